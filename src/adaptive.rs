@@ -0,0 +1,131 @@
+/// Criteria of the adaptive refinement of a sweep
+///
+/// Values of alpha are indexed on the finest grid the sweep can reach,
+/// which halves each interval of the initial grid a given number of times:
+/// the initial points have indices multiple of a power of 2, and a point
+/// inserted between two others gets the index in the middle of theirs, until
+/// they are neighbours on the finest grid. Indices are thus stable across
+/// rounds, and can be used to derive the seeds of the samples.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Refinement {
+    /// Target half-width of the confidence intervals
+    pub precision: f64,
+    /// Maximum number of samples of a value of alpha
+    pub max_samples: u32,
+    /// Range of probabilities considered part of the transition
+    pub band: (f64, f64),
+}
+
+/// Current estimate of the share of satisfiable formulas for a value of alpha
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Estimate {
+    pub alpha_idx: usize,
+    pub samples: u32,
+    pub satisfiable: u32,
+    pub lower: f64,
+    pub upper: f64,
+}
+
+/// Work to carry out in a round of the adaptive sweep
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Round {
+    /// Additional samples for existing points, as `(alpha_idx, samples)`
+    pub samples: Vec<(usize, u32)>,
+    /// Indices of the values of alpha to insert in the grid
+    pub insertions: Vec<usize>,
+}
+
+impl Round {
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty() && self.insertions.is_empty()
+    }
+}
+
+impl Refinement {
+    /// Plan the next round from the estimates of the points sorted by
+    /// index: the samples of the points of the transition (whose estimate
+    /// lies within the band) and whose interval is wider than the target
+    /// precision are doubled (up to `max_samples`), and a point is inserted
+    /// between two neighbours whose estimates straddle the band, as long as
+    /// the grid can still be refined there.
+    pub fn next_round(&self, estimates: &[Estimate]) -> Round {
+        let probability = |estimate: &Estimate| estimate.satisfiable as f64 / estimate.samples.max(1) as f64;
+        let samples = estimates
+            .iter()
+            .filter(|estimate| {
+                let p = probability(estimate);
+                p > self.band.0 && p < self.band.1
+            })
+            .filter(|estimate| (estimate.upper - estimate.lower) / 2. > self.precision)
+            .filter(|estimate| estimate.samples < self.max_samples)
+            .map(|estimate| (estimate.alpha_idx, estimate.samples.max(1).min(self.max_samples - estimate.samples)))
+            .collect();
+        let insertions = estimates
+            .windows(2)
+            .filter(|pair| pair[1].alpha_idx - pair[0].alpha_idx > 1)
+            .filter(|pair| {
+                let (p, q) = (probability(&pair[0]), probability(&pair[1]));
+                p.max(q) > self.band.0 && p.min(q) < self.band.1
+            })
+            .map(|pair| (pair[0].alpha_idx + pair[1].alpha_idx) / 2)
+            .collect();
+        Round { samples, insertions }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats::Interval;
+
+    const REFINEMENT: Refinement = Refinement {
+        precision: 0.05,
+        max_samples: 400,
+        band: (0.05, 0.95),
+    };
+
+    fn estimate(alpha_idx: usize, satisfiable: u32, samples: u32) -> Estimate {
+        let (lower, upper) = Interval::Wilson.bounds(satisfiable, samples, 0.95);
+        Estimate {
+            alpha_idx,
+            samples,
+            satisfiable,
+            lower,
+            upper,
+        }
+    }
+
+    #[test]
+    fn test_next_round() {
+        // Samples are only added in the transition, and points are inserted
+        // on both sides of the point in the middle of it
+        let estimates = [estimate(0, 100, 100), estimate(4, 100, 100), estimate(8, 50, 100), estimate(12, 0, 100)];
+        assert_eq!(
+            Round {
+                samples: vec![(8, 100)],
+                insertions: vec![6, 10],
+            },
+            REFINEMENT.next_round(&estimates)
+        );
+
+        // The number of samples is capped, and the grid cannot be refined
+        // further between neighbouring indices
+        let estimates = [estimate(7, 200, 200), estimate(8, 150, 300), estimate(9, 0, 200)];
+        assert_eq!(
+            Round {
+                samples: vec![(8, 100)],
+                insertions: vec![],
+            },
+            REFINEMENT.next_round(&estimates)
+        );
+
+        // Points outside of the transition are not doubled, however wide
+        // their intervals
+        let estimates = [estimate(0, 5, 5), estimate(1, 0, 5)];
+        assert!(REFINEMENT.next_round(&estimates).is_empty());
+
+        // Nothing left to do once the target precision is reached
+        let estimates = [estimate(7, 400, 400), estimate(8, 200, 400), estimate(9, 0, 400)];
+        assert!(REFINEMENT.next_round(&estimates).is_empty());
+    }
+}
@@ -0,0 +1,341 @@
+use crate::cnf::CNFFormula;
+use crate::propagate::{self, variable, ClauseRef, Literal, Propagator};
+use crate::solver::{Solution, Solver, Statistics};
+
+const ACTIVITY_DECAY: f64 = 0.95;
+
+/// Number of conflicts per unit of the Luby restart sequence
+const RESTART_UNIT: usize = 100;
+
+/// Number of learned clauses kept before the first reduction of the
+/// clause database, and its increment after each reduction
+const LEARNED_LIMIT: usize = 2000;
+const LEARNED_LIMIT_INCREMENT: usize = 300;
+
+/// Solver implementing Conflict-Driven Clause Learning as described
+/// in <https://en.wikipedia.org/wiki/Conflict-driven_clause_learning>:
+/// each conflict is analysed up to its first unique implication point
+/// (1-UIP), the resulting clause is learned, and the search backjumps
+/// to the second highest decision level of the learned clause. Unit
+/// propagation relies on the watched literals of `Propagator`, decisions
+/// follow the VSIDS heuristic with phase saving, the search is restarted
+/// following the Luby sequence, and the longest half of the learned clauses
+/// is regularly deleted. Variables which do not occur in the formula
+/// are left as `None` ("don't care").
+pub struct CdclSolver;
+
+impl Solver for CdclSolver {
+    fn name(&self) -> &'static str {
+        "cdcl"
+    }
+
+    fn solve(&self, formula: &CNFFormula) -> Solution {
+        match Search::new(formula) {
+            Some(search) => search.run(),
+            None => Solution {
+                model: None,
+                statistics: Statistics::default(),
+            },
+        }
+    }
+}
+
+/// State of a variable during conflict analysis
+#[derive(Clone, Copy, PartialEq)]
+enum Mark {
+    Unseen,
+    /// Part of the learned clause
+    Seen,
+    /// Implied by the learned clause
+    Redundant,
+    /// Not implied by the learned clause
+    Failed,
+}
+
+/// State of the CDCL search
+struct Search {
+    propagator: Propagator,
+    statistics: Statistics,
+    learned_limit: usize,
+    activity: Vec<f64>,
+    increment: f64,
+    phases: Vec<bool>,
+    occurring: Vec<bool>,
+}
+
+impl Search {
+    fn new(formula: &CNFFormula) -> Option<Self> {
+        let propagator = Propagator::new(formula)?;
+        let n = propagator.num_variables();
+        let mut occurring = vec![false; n];
+        for literal in formula.clauses.iter().flat_map(|clause| clause.literals.iter()) {
+            occurring[literal.variable as usize] = true;
+        }
+        Some(Search {
+            propagator,
+            statistics: Statistics::default(),
+            learned_limit: LEARNED_LIMIT,
+            activity: vec![0.; n],
+            increment: 1.,
+            phases: vec![false; n],
+            occurring,
+        })
+    }
+
+    fn run(mut self) -> Solution {
+        let mut restarts = 0;
+        let mut conflicts = 0;
+        let model = loop {
+            if let Some(conflict) = self.propagator.propagate() {
+                self.statistics.conflicts += 1;
+                if self.propagator.level() == 0 {
+                    break None;
+                }
+                conflicts += 1;
+                let (learned, level) = self.analyze(conflict);
+                self.statistics.backtracks += 1;
+                self.backtrack(level);
+                if learned.len() == 1 {
+                    self.propagator.assign(learned[0], None);
+                } else {
+                    let reason = self.propagator.learn(&learned);
+                    self.propagator.assign(learned[0], Some(reason));
+                    if self.propagator.num_learned() > self.learned_limit {
+                        self.propagator.reduce_learned();
+                        self.learned_limit += LEARNED_LIMIT_INCREMENT;
+                    }
+                }
+                self.increment /= ACTIVITY_DECAY;
+                if conflicts >= RESTART_UNIT * luby(restarts) {
+                    conflicts = 0;
+                    restarts += 1;
+                    self.backtrack(0);
+                }
+            } else {
+                match self.pick_branch_variable() {
+                    Some(variable) => {
+                        self.statistics.decisions += 1;
+                        self.propagator.new_level();
+                        let depth = self.propagator.level() as u64;
+                        self.statistics.max_depth = self.statistics.max_depth.max(depth);
+                        self.propagator
+                            .assign(2 * variable as Literal + !self.phases[variable] as Literal, None);
+                    }
+                    None => break Some(self.propagator.assignment().clone()),
+                }
+            }
+        };
+        self.statistics.propagations = self.propagator.propagations();
+        Solution {
+            model,
+            statistics: self.statistics,
+        }
+    }
+
+    /// Analyse a conflict, returning the 1-UIP learned clause (with its
+    /// asserting literal first) and the decision level to backjump to
+    fn analyze(&mut self, conflict: ClauseRef) -> (Vec<Literal>, usize) {
+        let level = self.propagator.level();
+        let mut seen = vec![Mark::Unseen; self.propagator.num_variables()];
+        let mut learned = vec![0];
+        let mut pending = 0;
+        let mut clause = conflict;
+        let mut implied: Option<Literal> = None;
+        let mut idx = self.propagator.trail().len();
+        loop {
+            for i in 0..self.propagator.clause(clause).len() {
+                let literal = self.propagator.clause(clause)[i];
+                if Some(literal) == implied {
+                    continue;
+                }
+                let variable = variable(literal);
+                if seen[variable] != Mark::Unseen || self.propagator.level_of(variable) == 0 {
+                    continue;
+                }
+                seen[variable] = Mark::Seen;
+                self.bump(variable);
+                if self.propagator.level_of(variable) == level {
+                    pending += 1;
+                } else {
+                    learned.push(literal);
+                }
+            }
+            // Walk back the trail to the next literal involved in the conflict
+            let trail = self.propagator.trail();
+            loop {
+                idx -= 1;
+                if seen[variable(trail[idx])] == Mark::Seen {
+                    break;
+                }
+            }
+            let literal = trail[idx];
+            seen[variable(literal)] = Mark::Unseen;
+            pending -= 1;
+            if pending == 0 {
+                learned[0] = literal ^ 1;
+                break;
+            }
+            implied = Some(literal);
+            clause = self
+                .propagator
+                .reason(variable(literal))
+                .expect("implied literals have a reason");
+        }
+
+        // Drop literals implied by other literals of the learned clause
+        let mut i = 1;
+        while i < learned.len() {
+            if self.is_redundant(variable(learned[i]), &mut seen) {
+                learned.swap_remove(i);
+            } else {
+                i += 1;
+            }
+        }
+
+        // Backjump to the highest level among the other literals
+        let mut backjump = 0;
+        for i in 1..learned.len() {
+            let other = self.propagator.level_of(variable(learned[i]));
+            if other > backjump {
+                backjump = other;
+                learned.swap(1, i);
+            }
+        }
+        (learned, backjump)
+    }
+
+    /// Check whether an assigned variable is implied by the literals
+    /// of the learned clause (marked as seen), recursively following
+    /// the reasons of the assignments
+    fn is_redundant(&self, variable: usize, seen: &mut [Mark]) -> bool {
+        let Some(reason) = self.propagator.reason(variable) else {
+            return false;
+        };
+        for &other in self.propagator.clause(reason)[1..].iter() {
+            let other = propagate::variable(other);
+            match seen[other] {
+                _ if self.propagator.level_of(other) == 0 => continue,
+                Mark::Seen | Mark::Redundant => continue,
+                Mark::Failed => return false,
+                Mark::Unseen => {
+                    if !self.is_redundant(other, seen) {
+                        seen[other] = Mark::Failed;
+                        return false;
+                    }
+                }
+            }
+        }
+        if seen[variable] == Mark::Unseen {
+            seen[variable] = Mark::Redundant;
+        }
+        true
+    }
+
+    /// Backtrack to a decision level, saving the phases of the
+    /// unassigned variables
+    fn backtrack(&mut self, level: usize) {
+        for &literal in self.propagator.trail_above(level) {
+            self.phases[variable(literal)] = literal & 1 == 0;
+        }
+        self.propagator.backtrack(level);
+    }
+
+    fn bump(&mut self, variable: usize) {
+        self.activity[variable] += self.increment;
+        if self.activity[variable] > 1e100 {
+            for activity in self.activity.iter_mut() {
+                *activity *= 1e-100;
+            }
+            self.increment *= 1e-100;
+        }
+    }
+
+    /// Choose the unassigned variable with the highest activity
+    fn pick_branch_variable(&self) -> Option<usize> {
+        let mut best: Option<usize> = None;
+        for variable in 0..self.activity.len() {
+            if self.propagator.is_assigned(variable) || !self.occurring[variable] {
+                continue;
+            }
+            if best.is_none_or(|best| self.activity[variable] > self.activity[best]) {
+                best = Some(variable);
+            }
+        }
+        best
+    }
+}
+
+/// Compute the i-th term of the Luby sequence (1, 1, 2, 1, 1, 2, 4, 1, ...)
+fn luby(mut i: usize) -> usize {
+    let mut size = 1;
+    let mut power = 1;
+    while size < i + 1 {
+        size = 2 * size + 1;
+        power *= 2;
+    }
+    while size - 1 != i {
+        size = (size - 1) / 2;
+        power /= 2;
+        i %= size;
+    }
+    power
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cnf::{CNFClause, CNFLiteral, Evaluation};
+    use crate::dpll::DpllSolver;
+    use crate::rand_cnf::generate_cnf;
+
+    #[test]
+    fn test_luby() {
+        let sequence: Vec<usize> = (0..15).map(luby).collect();
+        assert_eq!(vec![1, 1, 2, 1, 1, 2, 4, 1, 1, 2, 1, 1, 2, 4, 8], sequence);
+    }
+
+    #[test]
+    fn test_trivial() {
+        assert_eq!(Some(vec![]), CdclSolver.solve(&CNFFormula { clauses: vec![] }).model);
+        let formula = CNFFormula {
+            clauses: vec![CNFClause { literals: vec![] }],
+        };
+        assert!(!CdclSolver.solve(&formula).is_sat());
+    }
+
+    #[test]
+    fn test_unsatisfiable() {
+        // All 8 clauses over 3 variables
+        let clauses = (0..8)
+            .map(|signs: u32| CNFClause {
+                literals: (0..3)
+                    .map(|variable| CNFLiteral {
+                        negate: signs >> variable & 1 == 1,
+                        variable,
+                    })
+                    .collect(),
+            })
+            .collect();
+        assert!(!CdclSolver.solve(&CNFFormula { clauses }).is_sat());
+    }
+
+    #[test]
+    fn test_random_against_dpll() {
+        for seed in 0..50 {
+            let formula = generate_cnf(3, 20, 4.3, Some(seed));
+            let model = CdclSolver.solve(&formula).model;
+            assert_eq!(DpllSolver.solve(&formula).is_sat(), model.is_some());
+            if let Some(model) = model {
+                assert_eq!(Evaluation::Satisfied, formula.evaluate(&model));
+            }
+        }
+    }
+
+    #[test]
+    fn test_random_large() {
+        let formula = generate_cnf(3, 200, 3.5, Some(42));
+        let model = CdclSolver.solve(&formula).model.unwrap();
+        assert_eq!(Evaluation::Satisfied, formula.evaluate(&model));
+        assert!(!CdclSolver.solve(&generate_cnf(3, 200, 6., Some(42))).is_sat());
+    }
+}
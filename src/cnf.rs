@@ -1,11 +1,47 @@
 use std::fmt;
 
-#[derive(Clone)]
+/// A (partial) assignment of truth values, indexed by variable
+///
+/// Variables set to `None` are unconstrained ("don't care").
+pub type Assignment = Vec<Option<bool>>;
+
+/// Truth value of a literal, clause or formula under a (partial) assignment
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Evaluation {
+    Satisfied,
+    Falsified,
+    Undetermined,
+}
+
+/// Semantics of a clause read as a constraint on its literals
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Semantics {
+    /// At least one literal is true, as in k-SAT
+    AtLeastOne,
+    /// At least one literal is true and one is false, as in NAE-SAT
+    NotAllEqual,
+    /// Exactly one literal is true, as in 1-in-k SAT
+    ExactlyOne,
+}
+
+#[derive(Clone, Debug, PartialEq)]
 pub struct CNFLiteral {
     pub negate: bool,
     pub variable: u32,
 }
 
+impl CNFLiteral {
+    /// Evaluate the literal, variables out of the assignment range
+    /// being considered unassigned
+    pub fn evaluate(&self, assignment: &[Option<bool>]) -> Evaluation {
+        match assignment.get(self.variable as usize).copied().flatten() {
+            Some(value) if value != self.negate => Evaluation::Satisfied,
+            Some(_) => Evaluation::Falsified,
+            None => Evaluation::Undetermined,
+        }
+    }
+}
+
 impl fmt::Display for CNFLiteral {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
@@ -17,11 +53,55 @@ impl fmt::Display for CNFLiteral {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct CNFClause {
     pub literals: Vec<CNFLiteral>,
 }
 
+impl CNFClause {
+    /// Evaluate the clause: satisfied as soon as one literal is,
+    /// falsified when all literals are
+    pub fn evaluate(&self, assignment: &[Option<bool>]) -> Evaluation {
+        let mut evaluation = Evaluation::Falsified;
+        for literal in self.literals.iter() {
+            match literal.evaluate(assignment) {
+                Evaluation::Satisfied => return Evaluation::Satisfied,
+                Evaluation::Undetermined => evaluation = Evaluation::Undetermined,
+                Evaluation::Falsified => (),
+            }
+        }
+        evaluation
+    }
+
+    /// Clauses equivalent to the clause read as a constraint with the given
+    /// semantics: a not-all-equal constraint also requires the negated
+    /// clause, and an exactly-one constraint a clause ¬a∨¬b for every pair
+    /// of literals a and b
+    pub fn encode(&self, semantics: Semantics) -> Vec<CNFClause> {
+        let negated = |literal: &CNFLiteral| CNFLiteral {
+            negate: !literal.negate,
+            variable: literal.variable,
+        };
+        let mut clauses = vec![self.clone()];
+        match semantics {
+            Semantics::AtLeastOne => (),
+            Semantics::NotAllEqual => clauses.push(CNFClause {
+                literals: self.literals.iter().map(negated).collect(),
+            }),
+            Semantics::ExactlyOne => {
+                for (idx, first) in self.literals.iter().enumerate() {
+                    for second in self.literals[idx + 1..].iter() {
+                        clauses.push(CNFClause {
+                            literals: vec![negated(first), negated(second)],
+                        });
+                    }
+                }
+            }
+        }
+        clauses
+    }
+}
+
 impl fmt::Display for CNFClause {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
@@ -36,11 +116,46 @@ impl fmt::Display for CNFClause {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct CNFFormula {
     pub clauses: Vec<CNFClause>,
 }
 
+impl CNFFormula {
+    /// Number of variables (actually an upper bound, as variables
+    /// are counted from 0 up to the largest one in use)
+    pub fn num_variables(&self) -> usize {
+        self.clauses
+            .iter()
+            .flat_map(|clause| clause.literals.iter())
+            .map(|literal| literal.variable)
+            .max()
+            .map_or(0, |x| x as usize + 1)
+    }
+
+    /// Evaluate the formula: falsified as soon as one clause is,
+    /// satisfied when all clauses are
+    pub fn evaluate(&self, assignment: &[Option<bool>]) -> Evaluation {
+        let mut evaluation = Evaluation::Satisfied;
+        for clause in self.clauses.iter() {
+            match clause.evaluate(assignment) {
+                Evaluation::Falsified => return Evaluation::Falsified,
+                Evaluation::Undetermined => evaluation = Evaluation::Undetermined,
+                Evaluation::Satisfied => (),
+            }
+        }
+        evaluation
+    }
+
+    /// CNF formula equivalent to the formula whose clauses are read as
+    /// constraints with the given semantics
+    pub fn encode(&self, semantics: Semantics) -> CNFFormula {
+        CNFFormula {
+            clauses: self.clauses.iter().flat_map(|clause| clause.encode(semantics)).collect(),
+        }
+    }
+}
+
 impl fmt::Display for CNFFormula {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
@@ -54,3 +169,106 @@ impl fmt::Display for CNFFormula {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn literal(negate: bool, variable: u32) -> CNFLiteral {
+        CNFLiteral { negate, variable }
+    }
+
+    /// Evaluate the clause read as a constraint with the given semantics,
+    /// against which its encoding is tested
+    fn evaluate_as(clause: &CNFClause, semantics: Semantics, assignment: &[Option<bool>]) -> Evaluation {
+        let count = |evaluation| {
+            clause
+                .literals
+                .iter()
+                .filter(|literal| literal.evaluate(assignment) == evaluation)
+                .count()
+        };
+        let (satisfied, falsified) = (count(Evaluation::Satisfied), count(Evaluation::Falsified));
+        let undetermined = clause.literals.len() - satisfied - falsified;
+        match semantics {
+            Semantics::AtLeastOne => clause.evaluate(assignment),
+            Semantics::NotAllEqual if satisfied > 0 && falsified > 0 => Evaluation::Satisfied,
+            Semantics::ExactlyOne if satisfied > 1 => Evaluation::Falsified,
+            Semantics::ExactlyOne if satisfied == 1 && undetermined == 0 => Evaluation::Satisfied,
+            _ if undetermined > 0 => Evaluation::Undetermined,
+            _ => Evaluation::Falsified,
+        }
+    }
+
+    #[test]
+    fn test_evaluate_literal() {
+        let assignment = vec![Some(true), Some(false), None];
+        assert_eq!(Evaluation::Satisfied, literal(false, 0).evaluate(&assignment));
+        assert_eq!(Evaluation::Falsified, literal(true, 0).evaluate(&assignment));
+        assert_eq!(Evaluation::Satisfied, literal(true, 1).evaluate(&assignment));
+        assert_eq!(Evaluation::Undetermined, literal(false, 2).evaluate(&assignment));
+        assert_eq!(Evaluation::Undetermined, literal(false, 3).evaluate(&assignment));
+    }
+
+    #[test]
+    fn test_evaluate_formula() {
+        // (x_{0}∨x_{1})∧(¬x_{1}∨x_{2})
+        let formula = CNFFormula {
+            clauses: vec![
+                CNFClause {
+                    literals: vec![literal(false, 0), literal(false, 1)],
+                },
+                CNFClause {
+                    literals: vec![literal(true, 1), literal(false, 2)],
+                },
+            ],
+        };
+        assert_eq!(Evaluation::Undetermined, formula.evaluate(&[None, None, None]));
+        assert_eq!(Evaluation::Undetermined, formula.evaluate(&[Some(true), Some(true), None]));
+        assert_eq!(Evaluation::Satisfied, formula.evaluate(&[Some(true), Some(false), None]));
+        assert_eq!(Evaluation::Falsified, formula.evaluate(&[Some(false), Some(false), None]));
+        assert_eq!(Evaluation::Falsified, formula.evaluate(&[None, Some(true), Some(false)]));
+        assert_eq!(Evaluation::Satisfied, formula.evaluate(&[Some(false), Some(true), Some(true)]));
+    }
+
+    #[test]
+    fn test_evaluate_as() {
+        // x_{0}∨¬x_{1}∨x_{2}
+        let clause = CNFClause {
+            literals: vec![literal(false, 0), literal(true, 1), literal(false, 2)],
+        };
+        let evaluate = |semantics, assignment: &[Option<bool>]| evaluate_as(&clause, semantics, assignment);
+        let all_true = [Some(true), Some(false), Some(true)];
+        assert_eq!(Evaluation::Satisfied, evaluate(Semantics::AtLeastOne, &all_true));
+        assert_eq!(Evaluation::Falsified, evaluate(Semantics::NotAllEqual, &all_true));
+        assert_eq!(Evaluation::Falsified, evaluate(Semantics::ExactlyOne, &all_true));
+        let one_true = [Some(false), Some(true), Some(true)];
+        assert_eq!(Evaluation::Satisfied, evaluate(Semantics::NotAllEqual, &one_true));
+        assert_eq!(Evaluation::Satisfied, evaluate(Semantics::ExactlyOne, &one_true));
+        let partial = [Some(true), None, None];
+        assert_eq!(Evaluation::Satisfied, evaluate(Semantics::AtLeastOne, &partial));
+        assert_eq!(Evaluation::Undetermined, evaluate(Semantics::NotAllEqual, &partial));
+        assert_eq!(Evaluation::Undetermined, evaluate(Semantics::ExactlyOne, &partial));
+        assert_eq!(Evaluation::Falsified, evaluate(Semantics::ExactlyOne, &[Some(true), Some(false), None]));
+        assert_eq!(Evaluation::Satisfied, evaluate(Semantics::NotAllEqual, &[Some(true), Some(true), None]));
+    }
+
+    #[test]
+    fn test_encode() {
+        // The encodings agree with the semantics on every assignment
+        let clause = CNFClause {
+            literals: vec![literal(false, 0), literal(true, 1), literal(false, 2), literal(true, 3)],
+        };
+        for semantics in [Semantics::AtLeastOne, Semantics::NotAllEqual, Semantics::ExactlyOne] {
+            let formula = CNFFormula {
+                clauses: clause.encode(semantics),
+            };
+            for bits in 0..16 {
+                let assignment: Vec<Option<bool>> = (0..4).map(|variable| Some(bits >> variable & 1 == 1)).collect();
+                assert_eq!(evaluate_as(&clause, semantics, &assignment), formula.evaluate(&assignment));
+            }
+        }
+        assert_eq!(2, clause.encode(Semantics::NotAllEqual).len());
+        assert_eq!(7, clause.encode(Semantics::ExactlyOne).len());
+    }
+}
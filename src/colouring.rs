@@ -0,0 +1,158 @@
+use rand::Rng;
+use std::cmp::Reverse;
+
+use crate::cnf::{CNFClause, CNFFormula, CNFLiteral};
+use crate::rand_cnf::seeded_rng;
+
+/// An undirected graph without loops nor multiple edges, as the list of its
+/// edges (v, w) with v < w between vertices numbered from 0
+#[derive(Clone, Debug, PartialEq)]
+pub struct Graph {
+    pub vertices: u32,
+    pub edges: Vec<(u32, u32)>,
+}
+
+impl Graph {
+    pub fn degrees(&self) -> Vec<u32> {
+        let mut degrees = vec![0; self.vertices as usize];
+        for &(v, w) in self.edges.iter() {
+            degrees[v as usize] += 1;
+            degrees[w as usize] += 1;
+        }
+        degrees
+    }
+}
+
+/// Sample an Erdős–Rényi graph G(n, c/n), whose n(n-1)/2 possible edges are
+/// drawn independently with probability c/n, c being about the average degree
+///
+/// Following Batagelj and Brandes (Phys. Rev. E 71, 036113), the pairs of
+/// vertices are scanned in order, skipping over geometrically distributed
+/// numbers of pairs, so that the time is proportional to the number of edges.
+pub fn generate_graph(n: u32, c: f32, seed: Option<u64>) -> Graph {
+    let mut rng = seeded_rng(seed);
+    let p = (c as f64 / n as f64).clamp(0., 1.);
+    let mut edges = vec![];
+    if p > 0. {
+        // The edge (v, w) follows the pairs (0, w), ..., (v - 1, w)
+        let (mut v, mut w): (i64, i64) = (-1, 1);
+        while w < n as i64 {
+            let skip = if p < 1. {
+                ((1. - rng.gen::<f64>()).ln() / (1. - p).ln()).floor() as i64
+            } else {
+                0
+            };
+            v += 1 + skip;
+            while v >= w && w < n as i64 {
+                v -= w;
+                w += 1;
+            }
+            if w < n as i64 {
+                edges.push((v as u32, w as u32));
+            }
+        }
+    }
+    Graph { vertices: n, edges }
+}
+
+/// Variable meaning that a vertex has a colour, among k colours
+pub fn colour_variable(vertex: u32, colour: u8, k: u8) -> u32 {
+    vertex * k as u32 + colour as u32
+}
+
+/// Encode the k-colourability of a graph into a CNF formula, whose variable
+/// v·k + i means that vertex v has colour i
+///
+/// Each vertex has at least one colour and at most one (a clause ¬a∨¬b for
+/// each pair of its colours), and the ends of an edge do not share a
+/// colour. With `symmetry_breaking`, the colours are numbered in order of
+/// their first appearance along the vertices sorted by decreasing degree,
+/// which does not change the satisfiability: the i-th vertex gets one of
+/// the first i + 1 colours.
+pub fn encode_colouring(graph: &Graph, k: u8, symmetry_breaking: bool) -> CNFFormula {
+    let literal = |vertex, colour, negate| CNFLiteral {
+        negate,
+        variable: colour_variable(vertex, colour, k),
+    };
+    let mut clauses = vec![];
+    for vertex in 0..graph.vertices {
+        clauses.push(CNFClause {
+            literals: (0..k).map(|colour| literal(vertex, colour, false)).collect(),
+        });
+        for first in 0..k {
+            for second in first + 1..k {
+                clauses.push(CNFClause {
+                    literals: vec![literal(vertex, first, true), literal(vertex, second, true)],
+                });
+            }
+        }
+    }
+    for &(v, w) in graph.edges.iter() {
+        for colour in 0..k {
+            clauses.push(CNFClause {
+                literals: vec![literal(v, colour, true), literal(w, colour, true)],
+            });
+        }
+    }
+    if symmetry_breaking {
+        let degrees = graph.degrees();
+        let mut order: Vec<u32> = (0..graph.vertices).collect();
+        order.sort_by_key(|&vertex| Reverse(degrees[vertex as usize]));
+        for (rank, &vertex) in order.iter().enumerate().take(k.into()) {
+            for colour in rank as u8 + 1..k {
+                clauses.push(CNFClause {
+                    literals: vec![literal(vertex, colour, true)],
+                });
+            }
+        }
+    }
+    CNFFormula { clauses }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dpll::DpllSolver;
+    use crate::solver::Solver;
+
+    #[test]
+    fn test_generate_graph() {
+        let graph = generate_graph(1000, 4., Some(42));
+        assert_eq!(graph, generate_graph(1000, 4., Some(42)));
+        // About c·n/2 edges, with a standard deviation of about 45
+        assert!((graph.edges.len() as i64 - 2000).abs() < 200);
+        assert!(graph.edges.iter().all(|&(v, w)| v < w && w < 1000));
+        assert!(graph.edges.windows(2).all(|pair| (pair[0].1, pair[0].0) < (pair[1].1, pair[1].0)));
+
+        assert!(generate_graph(10, 0., Some(42)).edges.is_empty());
+        assert_eq!(45, generate_graph(10, 10., Some(42)).edges.len());
+    }
+
+    #[test]
+    fn test_encode() {
+        let triangle = Graph {
+            vertices: 3,
+            edges: vec![(0, 1), (0, 2), (1, 2)],
+        };
+        for symmetry_breaking in [false, true] {
+            assert!(!DpllSolver.solve(&encode_colouring(&triangle, 2, symmetry_breaking)).is_sat());
+            let model = DpllSolver.solve(&encode_colouring(&triangle, 3, symmetry_breaking)).model.unwrap();
+            let colours: Vec<u8> = (0..3)
+                .map(|vertex| (0..3).find(|&colour| model[colour_variable(vertex, colour, 3) as usize] == Some(true)).unwrap())
+                .collect();
+            assert!(triangle.edges.iter().all(|&(v, w)| colours[v as usize] != colours[w as usize]));
+        }
+    }
+
+    #[test]
+    fn test_symmetry_breaking() {
+        // Around the threshold of 3-colouring, about 4.69
+        for seed in 0..20 {
+            let graph = generate_graph(30, 4.7, Some(seed));
+            assert_eq!(
+                DpllSolver.solve(&encode_colouring(&graph, 3, false)).is_sat(),
+                DpllSolver.solve(&encode_colouring(&graph, 3, true)).is_sat()
+            );
+        }
+    }
+}
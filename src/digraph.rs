@@ -1,39 +1,68 @@
 use std::collections::HashSet;
 
-use crate::cnf::{CNFFormula, CNFLiteral};
+use crate::cnf::{Assignment, CNFFormula, CNFLiteral};
+use crate::solver::{Solution, Solver, Statistics};
 
 
-/// Determine satisfiability of a 2-SAT problem
+/// Solver for 2-SAT problems
 /// 
-/// This function will determine the satisfiability
+/// This solver will determine the satisfiability
 /// of a CNF formula with clauses of size 2, using
 /// the directed graph of implications for the formula,
 /// as described in <https://cp-algorithms.com/graph/2SAT.html>
-pub fn digraph_2sat(formula: &CNFFormula) -> bool {
+pub struct DigraphSolver;
+
+impl Solver for DigraphSolver {
+    fn name(&self) -> &'static str {
+        "2sat"
+    }
+
+    fn solve(&self, formula: &CNFFormula) -> Solution {
+        Solution {
+            model: digraph_2sat_model(formula).ok(),
+            statistics: Statistics::default(),
+        }
+    }
+}
+
+/// Find a satisfying assignment of a 2-SAT problem
+///
+/// Each variable is assigned from the topological order of the
+/// strongly connected components of its two literals: `x` is true
+/// when its component comes after the one of `¬x`. If the formula
+/// is unsatisfiable, the variable whose literals `x` and `¬x` lie
+/// in the same component is returned as an error.
+pub fn digraph_2sat_model(formula: &CNFFormula) -> Result<Assignment, u32> {
     assert!(formula.clauses.iter().all(|clause| clause.literals.len() == 2));
 
-    // Number of variables (actually an upper bound)
-    let n = formula
-        .clauses
-        .iter()
-        .flat_map(|clause| clause.literals.iter())
-        .map(|literal| literal.variable)
-        .max()
-        .map_or_else(|| 0, |x| x + 1) as usize;
+    let n = formula.num_variables();
 
     let (adj, adj_t) = build_digraph(formula, 2 * n);
 
     // See <https://en.wikipedia.org/wiki/Kosaraju%27s_algorithm>
+    // Components are numbered in topological order of the condensation.
     let mut ordering: Vec<usize> = vec![];
     let mut visited: HashSet<usize> = HashSet::new();
     for vertex in 0..(2 * n) {
         dfs1(&adj, vertex, &mut visited, &mut ordering);
     }
     let mut components = vec![None::<usize>; 2 * n];
+    let mut count = 0;
     for vertex in ordering.into_iter().rev() {
-        dfs2(&adj_t, vertex, vertex, &mut components);
+        if components[vertex].is_none() {
+            dfs2(&adj_t, count, vertex, &mut components);
+            count += 1;
+        }
     }
-    return (0..n).all(|i| components[2 * i] != components[2 * i + 1]);
+    (0..n)
+        .map(|i| {
+            if components[2 * i] == components[2 * i + 1] {
+                Err(i as u32)
+            } else {
+                Ok(Some(components[2 * i] > components[2 * i + 1]))
+            }
+        })
+        .collect()
 }
 
 #[inline(always)]
@@ -56,7 +85,7 @@ fn build_digraph(formula: &CNFFormula, vertices: usize) -> (Vec<HashSet<usize>>,
         adj_t[compute_idx(&clause.literals[0], false)].insert(compute_idx(&clause.literals[1], true));
     }
 
-    return (adj, adj_t);
+    (adj, adj_t)
 }
 
 fn dfs1(adj: &Vec<HashSet<usize>>, vertex: usize, visited: &mut HashSet<usize>, ordering: &mut Vec<usize>) {
@@ -70,13 +99,13 @@ fn dfs1(adj: &Vec<HashSet<usize>>, vertex: usize, visited: &mut HashSet<usize>,
     ordering.push(vertex);
 }
 
-fn dfs2(adj_t: &Vec<HashSet<usize>>, root: usize, vertex: usize, components: &mut Vec<Option<usize>>) {
+fn dfs2(adj_t: &Vec<HashSet<usize>>, component: usize, vertex: usize, components: &mut Vec<Option<usize>>) {
     if components[vertex].is_some() {
         return;
     }
-    components[vertex] = Some(root);
+    components[vertex] = Some(component);
     for neighbour in adj_t[vertex].iter() {
-        dfs2(adj_t, root, *neighbour, components);
+        dfs2(adj_t, component, *neighbour, components);
     }
 }
 
@@ -128,18 +157,54 @@ mod tests {
                 },
             ],
         };
-        assert!(digraph_2sat(&formula));
+        assert!(digraph_2sat_model(&formula).is_ok());
     }
 
     #[test]
     fn test_random_satisfiable() {
         let formula = generate_cnf(2, 25, 0.5, Some(42));
-        assert!(digraph_2sat(&formula));
+        assert!(digraph_2sat_model(&formula).is_ok());
     }
 
     #[test]
     fn test_random_unsatisfiable() {
         let formula = generate_cnf(2, 25, 2., Some(42));
-        assert!(!digraph_2sat(&formula));
+        assert!(digraph_2sat_model(&formula).is_err());
+    }
+
+    #[test]
+    fn test_random_model() {
+        let formula = generate_cnf(2, 100, 0.8, Some(42));
+        let model = digraph_2sat_model(&formula).unwrap();
+        assert!(formula.clauses.iter().all(|clause| clause
+            .literals
+            .iter()
+            .any(|literal| model[literal.variable as usize] == Some(!literal.negate))));
+    }
+
+    #[test]
+    fn test_contradiction() {
+        // (x_0∨x_1)∧(x_0∨¬x_1)∧(¬x_0∨x_2)∧(¬x_0∨¬x_2)
+        let clause = |a: (bool, u32), b: (bool, u32)| CNFClause {
+            literals: vec![
+                CNFLiteral {
+                    negate: a.0,
+                    variable: a.1,
+                },
+                CNFLiteral {
+                    negate: b.0,
+                    variable: b.1,
+                },
+            ],
+        };
+        let formula = CNFFormula {
+            clauses: vec![
+                clause((false, 0), (false, 1)),
+                clause((false, 0), (true, 1)),
+                clause((true, 0), (false, 2)),
+                clause((true, 0), (true, 2)),
+            ],
+        };
+        assert_eq!(Err(0), digraph_2sat_model(&formula));
     }
 }
@@ -0,0 +1,322 @@
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Write};
+
+use crate::cnf::{Assignment, CNFClause, CNFFormula, CNFLiteral};
+use crate::xorsat::XorFormula;
+
+/// Error raised when parsing a malformed DIMACS file
+#[derive(Debug, PartialEq)]
+pub struct DimacsError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl DimacsError {
+    fn new(line: usize, column: usize, message: impl Into<String>) -> Self {
+        DimacsError {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for DimacsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, column {}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl Error for DimacsError {}
+
+/// Parse a CNF formula in DIMACS format
+///
+/// The format is described in <https://jix.github.io/varisat/manual/0.2.0/formats/dimacs.html>:
+/// comment lines start with `c`, a `p cnf <variables> <clauses>` header
+/// precedes the clauses, and each clause is a list of non-zero integers
+/// terminated by `0`, possibly spanning several lines. As in SATLIB
+/// benchmarks, a line starting with `%` ends the formula.
+///
+/// DIMACS variables are numbered from 1, they are shifted to start from 0.
+/// The number of variables declared in the header is returned along with
+/// the formula.
+pub fn parse_dimacs(input: &str) -> Result<(CNFFormula, u32), DimacsError> {
+    let mut header: Option<(usize, u32, usize)> = None;
+    let mut clauses = vec![];
+    let mut literals = vec![];
+    let mut last_line = 0;
+
+    for (idx, line) in input.lines().enumerate() {
+        let line_nb = idx + 1;
+        last_line = line_nb;
+        let mut tokens = tokenize(line).peekable();
+        match tokens.peek().copied() {
+            None => continue,
+            Some((_, token)) if token.starts_with('c') => continue,
+            Some((_, token)) if token.starts_with('%') => break,
+            Some((column, "p")) => {
+                if header.is_some() {
+                    return Err(DimacsError::new(line_nb, column, "duplicate problem line"));
+                }
+                tokens.next();
+                header = Some(parse_header(line_nb, column, tokens)?);
+            }
+            Some(_) => {
+                let Some((_, variables, _)) = header else {
+                    return Err(DimacsError::new(line_nb, 1, "missing problem line before clauses"));
+                };
+                for (column, token) in tokens {
+                    let value: i64 = token.parse().map_err(|_| {
+                        DimacsError::new(line_nb, column, format!("invalid literal `{}`", token))
+                    })?;
+                    if value == 0 {
+                        clauses.push(CNFClause { literals });
+                        literals = vec![];
+                        continue;
+                    }
+                    if value.unsigned_abs() > variables as u64 {
+                        return Err(DimacsError::new(
+                            line_nb,
+                            column,
+                            format!("variable {} exceeds the {} declared variables", value.abs(), variables),
+                        ));
+                    }
+                    literals.push(CNFLiteral {
+                        negate: value < 0,
+                        variable: (value.unsigned_abs() - 1) as u32,
+                    });
+                }
+            }
+        }
+    }
+
+    // Tolerate a missing `0` after the last clause
+    if !literals.is_empty() {
+        clauses.push(CNFClause { literals });
+    }
+
+    match header {
+        None => Err(DimacsError::new(last_line.max(1), 1, "missing problem line")),
+        Some((line_nb, _, expected)) if expected != clauses.len() => Err(DimacsError::new(
+            line_nb,
+            1,
+            format!("problem line declares {} clauses but {} were found", expected, clauses.len()),
+        )),
+        Some((_, variables, _)) => Ok((CNFFormula { clauses }, variables)),
+    }
+}
+
+/// Write a CNF formula in DIMACS format, declaring `variables` variables
+/// (the actual number of variables of the formula is used if larger)
+pub fn write_dimacs(writer: &mut impl Write, formula: &CNFFormula, variables: u32) -> io::Result<()> {
+    let variables = variables.max(formula.num_variables() as u32);
+    writeln!(writer, "p cnf {} {}", variables, formula.clauses.len())?;
+    for clause in formula.clauses.iter() {
+        for literal in clause.literals.iter() {
+            write!(
+                writer,
+                "{}{} ",
+                if literal.negate { "-" } else { "" },
+                literal.variable + 1
+            )?;
+        }
+        writeln!(writer, "0")?;
+    }
+    Ok(())
+}
+
+/// Write an XOR-SAT formula in the extended DIMACS format of CryptoMiniSat,
+/// declaring `variables` variables (the actual number of variables of the
+/// formula is used if larger)
+///
+/// Each constraint is written as a clause prefixed with `x`, meaning that
+/// the XOR of its literals is true: the first variable of a constraint of
+/// parity 0 is negated, e.g. `x1 -2 0` for x_0⊕x_1 = 0.
+pub fn write_xor_dimacs(writer: &mut impl Write, formula: &XorFormula, variables: u32) -> io::Result<()> {
+    let variables = variables.max(formula.num_variables() as u32);
+    writeln!(writer, "p cnf {} {}", variables, formula.constraints.len())?;
+    for constraint in formula.constraints.iter() {
+        write!(writer, "x")?;
+        for (idx, &variable) in constraint.variables.iter().enumerate() {
+            let negate = idx == 0 && !constraint.parity;
+            write!(writer, "{}{} ", if negate { "-" } else { "" }, variable + 1)?;
+        }
+        writeln!(writer, "0")?;
+    }
+    Ok(())
+}
+
+/// Complete a model to a value for each of the `variables` variables (or
+/// more, if the model is longer), unassigned ("don't care") variables
+/// being set to false
+pub fn complete_model(model: &Assignment, variables: u32) -> Assignment {
+    let variables = variables.max(model.len() as u32);
+    (0..variables)
+        .map(|variable| Some(model.get(variable as usize).copied().flatten().unwrap_or(false)))
+        .collect()
+}
+
+/// Write the outcome of a solver in the SAT competition format
+///
+/// See <https://satcompetition.github.io/2024/output.html>: a `s SATISFIABLE`
+/// or `s UNSATISFIABLE` line, followed for satisfiable formulas by `v` lines
+/// listing the value of each of the `variables` variables, and ending with `0`.
+/// The model is completed as by `complete_model`.
+pub fn write_solution(writer: &mut impl Write, model: Option<&Assignment>, variables: u32) -> io::Result<()> {
+    let Some(model) = model else {
+        return writeln!(writer, "s UNSATISFIABLE");
+    };
+    writeln!(writer, "s SATISFIABLE")?;
+    let values = complete_model(model, variables)
+        .into_iter()
+        .enumerate()
+        .map(|(variable, value)| {
+            if value == Some(true) {
+                format!("{}", variable + 1)
+            } else {
+                format!("-{}", variable + 1)
+            }
+        })
+        .chain(std::iter::once("0".to_string()))
+        .collect::<Vec<String>>();
+    for chunk in values.chunks(10) {
+        writeln!(writer, "v {}", chunk.join(" "))?;
+    }
+    Ok(())
+}
+
+/// Split a line into whitespace-separated tokens, along with their (1-based) column
+fn tokenize(line: &str) -> impl Iterator<Item = (usize, &str)> {
+    line.split_whitespace().map(move |token| {
+        let offset = token.as_ptr() as usize - line.as_ptr() as usize;
+        (line[..offset].chars().count() + 1, token)
+    })
+}
+
+fn parse_header<'a>(
+    line_nb: usize,
+    column: usize,
+    mut tokens: impl Iterator<Item = (usize, &'a str)>,
+) -> Result<(usize, u32, usize), DimacsError> {
+    match tokens.next() {
+        Some((_, "cnf")) => (),
+        Some((column, token)) => {
+            return Err(DimacsError::new(line_nb, column, format!("unsupported format `{}`", token)))
+        }
+        None => return Err(DimacsError::new(line_nb, column, "missing format in problem line")),
+    }
+    let variables = match tokens.next() {
+        Some((column, token)) => token.parse().map_err(|_| {
+            DimacsError::new(line_nb, column, format!("invalid number of variables `{}`", token))
+        })?,
+        None => return Err(DimacsError::new(line_nb, column, "missing number of variables")),
+    };
+    let clauses = match tokens.next() {
+        Some((column, token)) => token.parse().map_err(|_| {
+            DimacsError::new(line_nb, column, format!("invalid number of clauses `{}`", token))
+        })?,
+        None => return Err(DimacsError::new(line_nb, column, "missing number of clauses")),
+    };
+    if let Some((column, token)) = tokens.next() {
+        return Err(DimacsError::new(line_nb, column, format!("unexpected `{}` in problem line", token)));
+    }
+    Ok((line_nb, variables, clauses))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rand_cnf::generate_cnf;
+    use crate::xorsat::XorConstraint;
+
+    #[test]
+    fn test_parse() {
+        let input = "c A small example\nc\np cnf 3 2\n1 -3 0\n2 3\n-1 0\n";
+        let (formula, variables) = parse_dimacs(input).unwrap();
+        assert_eq!(3, variables);
+        assert_eq!("(x_{0}∨¬x_{2})∧(x_{1}∨x_{2}∨¬x_{0})", formula.to_string());
+    }
+
+    #[test]
+    fn test_parse_satlib() {
+        let input = "c SATLIB style\np  cnf  2  2\n 1 2 0\n-1 -2 0\n%\n0\n\n";
+        let (formula, _) = parse_dimacs(input).unwrap();
+        assert_eq!("(x_{0}∨x_{1})∧(¬x_{0}∨¬x_{1})", formula.to_string());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Err(DimacsError::new(1, 1, "missing problem line before clauses")),
+            parse_dimacs("1 2 0\n")
+        );
+        assert_eq!(
+            Err(DimacsError::new(1, 3, "unsupported format `sat`")),
+            parse_dimacs("p sat 2 1\n")
+        );
+        assert_eq!(
+            Err(DimacsError::new(2, 4, "invalid literal `x`")),
+            parse_dimacs("p cnf 2 1\n1  x 0\n")
+        );
+        assert_eq!(
+            Err(DimacsError::new(3, 3, "variable 3 exceeds the 2 declared variables")),
+            parse_dimacs("p cnf 2 2\n1 2 0\n1 3 0\n")
+        );
+        assert_eq!(
+            Err(DimacsError::new(2, 1, "problem line declares 2 clauses but 1 were found")),
+            parse_dimacs("c\np cnf 2 2\n1 2 0\n")
+        );
+        assert_eq!(
+            Err(DimacsError::new(2, 1, "duplicate problem line")),
+            parse_dimacs("p cnf 2 1\np cnf 2 1\n1 2 0\n")
+        );
+    }
+
+    #[test]
+    fn test_write_solution() {
+        let mut output = vec![];
+        write_solution(&mut output, None, 3).unwrap();
+        assert_eq!("s UNSATISFIABLE\n", String::from_utf8(output).unwrap());
+
+        let mut output = vec![];
+        let model = vec![Some(true), None, Some(false), Some(true)];
+        write_solution(&mut output, Some(&model), 12).unwrap();
+        assert_eq!(
+            "s SATISFIABLE\nv 1 -2 -3 4 -5 -6 -7 -8 -9 -10\nv -11 -12 0\n",
+            String::from_utf8(output).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_write_xor() {
+        let formula = XorFormula {
+            constraints: vec![
+                XorConstraint {
+                    variables: vec![0, 2],
+                    parity: true,
+                },
+                XorConstraint {
+                    variables: vec![1, 2, 3],
+                    parity: false,
+                },
+            ],
+        };
+        let mut output = vec![];
+        write_xor_dimacs(&mut output, &formula, 3).unwrap();
+        assert_eq!("p cnf 4 2\nx1 3 0\nx-2 3 4 0\n", String::from_utf8(output).unwrap());
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let formula = generate_cnf(3, 10, 4., Some(42));
+        let mut output = vec![];
+        write_dimacs(&mut output, &formula, 12).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with("p cnf 12 40\n"));
+        let (parsed, variables) = parse_dimacs(&output).unwrap();
+        assert_eq!(12, variables);
+        assert_eq!(formula, parsed);
+    }
+}
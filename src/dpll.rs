@@ -1,165 +1,136 @@
-use itertools::Itertools;
-use std::collections::HashSet;
+use crate::cnf::{CNFFormula, Evaluation};
+use crate::propagate::{Literal, Propagator};
+use crate::solver::{Solution, Solver, Statistics};
 
-use crate::cnf::{CNFClause, CNFFormula, CNFLiteral};
-
-/// Determine satisfiability of a CNF formule
-///
-/// This function implements the DPLL algorithm as described
+/// Solver implementing the DPLL algorithm as described
 /// in <https://en.wikipedia.org/wiki/DPLL_algorithm#The_algorithm>
-pub fn dpll_sat(init_formula: &CNFFormula) -> bool {
-    let mut formula = init_formula.clone();
+pub struct DpllSolver;
 
-    // Unit propagation
-    while let Some(literal) = formula
-        .clauses
-        .iter()
-        .find(|clause| clause.literals.len() == 1)
-        .map(|clause| &clause.literals[0])
-    {
-        formula = dpll_unit_propagate(&formula, literal)
+impl Solver for DpllSolver {
+    fn name(&self) -> &'static str {
+        "dpll"
     }
 
-    // Pure litteral elimination
-    let mut variables = HashSet::new();
-    let mut pos_variables = HashSet::new();
-    let mut neg_variables = HashSet::new();
-    for literal in formula
-        .clauses
-        .to_vec()
-        .into_iter()
-        .flat_map(|clause| clause.literals)
-    {
-        variables.insert(literal.variable);
-        if literal.negate {
-            neg_variables.insert(literal.variable);
-        } else {
-            pos_variables.insert(literal.variable);
-        }
+    /// Values are recorded as the DPLL recursion makes decisions, unit
+    /// propagations and pure literal eliminations; variables which were
+    /// never assigned are left as `None` ("don't care"), except those of
+    /// the tautologies dropped by the propagator, which are set to false.
+    fn solve(&self, formula: &CNFFormula) -> Solution {
+        let mut statistics = Statistics::default();
+        let model = match Propagator::new(formula) {
+            Some(mut propagator) => {
+                let satisfiable = dpll(&mut propagator, &mut statistics, 0);
+                statistics.propagations = propagator.propagations();
+                satisfiable.then(|| {
+                    let mut model = propagator.assignment().clone();
+                    // Only tautologies can be left undetermined, and any
+                    // value of their variables satisfies them
+                    for clause in formula.clauses.iter() {
+                        if clause.evaluate(&model) != Evaluation::Satisfied {
+                            for literal in clause.literals.iter() {
+                                model[literal.variable as usize].get_or_insert(false);
+                            }
+                        }
+                    }
+                    model
+                })
+            }
+            None => None,
+        };
+        Solution { model, statistics }
     }
+}
 
-    for variable in pos_variables.difference(&neg_variables) {
-        formula = dpll_pure_literal_elimination(
-            &formula,
-            &CNFLiteral {
-                variable: *variable,
-                negate: false,
-            },
-        );
-    }
-    for variable in neg_variables.difference(&pos_variables) {
-        formula = dpll_pure_literal_elimination(
-            &formula,
-            &CNFLiteral {
-                variable: *variable,
-                negate: true,
-            },
-        );
+/// Recursive step of the DPLL algorithm, on top of the watched literals
+/// of `Propagator`: on return, the assignments made by a failed branch
+/// have been undone. `depth` is the number of decisions leading to this call.
+fn dpll(propagator: &mut Propagator, statistics: &mut Statistics, depth: u64) -> bool {
+    statistics.max_depth = statistics.max_depth.max(depth);
+
+    // Unit propagation
+    if propagator.propagate().is_some() {
+        statistics.conflicts += 1;
+        return false;
     }
 
-    // Stopping conditions
-    if formula.clauses.is_empty() {
+    // Pure litteral elimination
+    let Some(occurrences) = dpll_occurrences(propagator) else {
         return true;
+    };
+    let pure_literals: Vec<Literal> = (0..occurrences.len() as Literal)
+        .filter(|&literal| occurrences[literal as usize] > 0 && occurrences[(literal ^ 1) as usize] == 0)
+        .collect();
+    for &literal in pure_literals.iter() {
+        propagator.assign(literal, None);
     }
-    if formula
-        .clauses
-        .iter()
-        .any(|clause| clause.literals.is_empty())
-    {
-        return false;
-    }
+    statistics.pure_literals += pure_literals.len() as u64;
+
+    // Stopping condition (pure literals cannot lead to a conflict)
+    let occurrences = if pure_literals.is_empty() {
+        occurrences
+    } else {
+        propagator.propagate();
+        match dpll_occurrences(propagator) {
+            Some(occurrences) => occurrences,
+            None => return true,
+        }
+    };
 
     // Recursive calls
-    let variable = dpll_choose_literal(&formula);
-    let mut clauses_pos = formula.clauses.to_vec();
-    clauses_pos.push(CNFClause {
-        literals: vec![CNFLiteral {
-            variable,
-            negate: false,
-        }],
-    });
-    let mut clauses_neg = formula.clauses.to_vec();
-    clauses_neg.push(CNFClause {
-        literals: vec![CNFLiteral {
-            variable,
-            negate: true,
-        }],
-    });
-    return dpll_sat(&CNFFormula {
-        clauses: clauses_pos,
-    }) || dpll_sat(&CNFFormula {
-        clauses: clauses_neg,
-    });
+    let variable = dpll_choose_literal(&occurrences);
+    let level = propagator.level();
+    for negate in [false, true] {
+        statistics.decisions += 1;
+        propagator.new_level();
+        propagator.assign(2 * variable + negate as Literal, None);
+        if dpll(propagator, statistics, depth + 1) {
+            return true;
+        }
+        statistics.backtracks += 1;
+        propagator.backtrack(level);
+    }
+    false
 }
 
-fn dpll_unit_propagate(formula: &CNFFormula, literal: &CNFLiteral) -> CNFFormula {
-    let clauses = formula
-        .clauses
-        .iter()
-        .map(|clause| {
-            match clause
-                .literals
-                .iter()
-                .find(|x| x.variable == literal.variable)
-            {
-                Some(&CNFLiteral { negate, .. }) => {
-                    if negate == literal.negate {
-                        None
-                    } else {
-                        Some(CNFClause {
-                            literals: clause
-                                .literals
-                                .iter()
-                                .filter(|x| x.variable != literal.variable)
-                                .map(|x| x.clone())
-                                .collect(),
-                        })
-                    }
-                }
-                None => Some(clause.clone()),
+/// Count the occurrences of each unassigned literal in the clauses which
+/// are not satisfied yet, or return `None` if all clauses are satisfied
+fn dpll_occurrences(propagator: &Propagator) -> Option<Vec<u32>> {
+    let mut occurrences = vec![0; 2 * propagator.num_variables()];
+    let mut satisfied = true;
+    for clause in propagator.original_clauses() {
+        if clause.iter().any(|&literal| propagator.value(literal) == Some(true)) {
+            continue;
+        }
+        satisfied = false;
+        for &literal in clause.iter() {
+            if propagator.value(literal).is_none() {
+                occurrences[literal as usize] += 1;
             }
-        })
-        .flatten()
-        .collect();
-    CNFFormula { clauses }
-}
-
-fn dpll_pure_literal_elimination(formula: &CNFFormula, literal: &CNFLiteral) -> CNFFormula {
-    let clauses = formula
-        .clauses
-        .iter()
-        .filter(|clause| {
-            clause
-                .literals
-                .iter()
-                .all(|x| x.variable != literal.variable)
-        })
-        .map(|clause| clause.clone())
-        .collect();
-    CNFFormula { clauses }
+        }
+    }
+    if satisfied {
+        None
+    } else {
+        Some(occurrences)
+    }
 }
 
-fn dpll_choose_literal(formula: &CNFFormula) -> u32 {
-    return unsafe {
-        formula
-            .clauses
-            .to_vec()
-            .into_iter()
-            .flat_map(|clause| clause.literals)
-            .map(|literal| literal.variable)
-            .sorted()
-            .chunk_by(|&x| x)
-            .into_iter()
-            .map(|(key, chunk)| (key, chunk.count()))
-            .max_by_key(|&(_, count)| count)
-            .map(|(idx, _)| idx)
-            .unwrap_unchecked()
-    };
+/// Choose the variable occurring the most in the remaining clauses
+fn dpll_choose_literal(occurrences: &[u32]) -> Literal {
+    let mut best = (0, 0);
+    for (variable, counts) in occurrences.chunks(2).enumerate() {
+        if counts[0] + counts[1] >= best.1 {
+            best = (variable as Literal, counts[0] + counts[1]);
+        }
+    }
+    best.0
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::cnf::{CNFClause, CNFLiteral};
+    use crate::rand_cnf::generate_cnf;
 
     #[test]
     fn test_satisfiable() {
@@ -201,7 +172,59 @@ mod tests {
                 },
             ],
         };
-        assert!(dpll_sat(&formula));
+        assert!(DpllSolver.solve(&formula).is_sat());
+    }
+
+    #[test]
+    fn test_satisfiable_model() {
+        let formula = generate_cnf(3, 20, 3., Some(42));
+        let solution = DpllSolver.solve(&formula);
+        assert!(solution.statistics.max_depth <= solution.statistics.decisions);
+        let model = solution.model.unwrap();
+        assert_eq!(20, model.len());
+        assert!(formula.clauses.iter().all(|clause| clause
+            .literals
+            .iter()
+            .any(|literal| model[literal.variable as usize] == Some(!literal.negate))));
+    }
+
+    #[test]
+    fn test_pure_literals() {
+        // x_0 only occurs positively, which satisfies both clauses
+        let clause = |negate| CNFClause {
+            literals: vec![
+                CNFLiteral {
+                    negate: false,
+                    variable: 0,
+                },
+                CNFLiteral { negate, variable: 1 },
+            ],
+        };
+        let solution = DpllSolver.solve(&CNFFormula {
+            clauses: vec![clause(false), clause(true)],
+        });
+        assert_eq!(Some(vec![Some(true), None]), solution.model);
+        assert_eq!(1, solution.statistics.pure_literals);
+        assert_eq!(0, solution.statistics.decisions);
+    }
+
+    #[test]
+    fn test_tautology() {
+        // x_0 only occurs in the tautology x_0∨¬x_0, which the propagator drops
+        let literal = |negate, variable| CNFLiteral { negate, variable };
+        let formula = CNFFormula {
+            clauses: vec![
+                CNFClause {
+                    literals: vec![literal(false, 0), literal(true, 0)],
+                },
+                CNFClause {
+                    literals: vec![literal(false, 1), literal(false, 2)],
+                },
+            ],
+        };
+        let model = DpllSolver.solve(&formula).model.unwrap();
+        assert_eq!(Some(false), model[0]);
+        assert_eq!(Evaluation::Satisfied, formula.evaluate(&model));
     }
 
     #[test]
@@ -338,6 +361,20 @@ mod tests {
                 },
             ],
         };
-        assert!(!dpll_sat(&formula));
+        let solution = DpllSolver.solve(&formula);
+        assert!(!solution.is_sat());
+        // Both values of the first two branch variables are refuted,
+        // the third variable being set by unit propagation
+        assert_eq!(
+            Statistics {
+                decisions: 6,
+                propagations: 4,
+                conflicts: 4,
+                pure_literals: 0,
+                backtracks: 6,
+                max_depth: 2,
+            },
+            solution.statistics
+        );
     }
 }
@@ -0,0 +1,194 @@
+use clap::ValueEnum;
+
+use crate::stats;
+
+/// Sigmoid model of the share of satisfiable formulas around the threshold
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum Model {
+    /// P(α) = 1 / (1 + exp((α - α_c) / w))
+    Logistic,
+    /// P(α) = erfc((α - α_c) / (w √2)) / 2, i.e. a normal distribution of thresholds
+    Erf,
+}
+
+impl Model {
+    /// Cumulative distribution function of the model, and its derivative
+    fn cdf(self, x: f64) -> (f64, f64) {
+        match self {
+            Model::Logistic => {
+                let p = 1. / (1. + (-x).exp());
+                (p, p * (1. - p))
+            }
+            Model::Erf => (stats::normal_cdf(x), (-x * x / 2.).exp() / (2. * std::f64::consts::PI).sqrt()),
+        }
+    }
+}
+
+/// Threshold and width of the transition fitted to the results of a sweep,
+/// with their standard errors
+#[derive(Clone, Debug, PartialEq)]
+pub struct Fit {
+    pub model: Model,
+    /// Value of alpha at which half of the formulas are satisfiable
+    pub alpha_c: f64,
+    pub alpha_c_error: f64,
+    /// Width w of the transition (negative if the share of satisfiable
+    /// formulas increases with the parameter)
+    pub width: f64,
+    pub width_error: f64,
+}
+
+impl Fit {
+    /// Share of satisfiable formulas predicted by the fitted model
+    pub fn probability(&self, alpha: f64) -> f64 {
+        self.model.cdf((self.alpha_c - alpha) / self.width).0
+    }
+}
+
+/// Fit a sigmoid model to binomial observations `(alpha, satisfiable, samples)`
+/// by maximum likelihood
+///
+/// The model is written as P(α) = F(β₀ + β₁α), a generalized linear model
+/// whose parameters are found by Fisher scoring, and whose covariance is the
+/// inverse of the Fisher information. The threshold α_c = -β₀/β₁ and the width
+/// w = -1/β₁ get their standard errors by the delta method. `None` is returned
+/// if the data do not show a decreasing transition (e.g. if the points are
+/// all satisfiable, or if the transition is sharper than the grid of alpha).
+pub fn fit(model: Model, observations: &[(f64, u32, u32)]) -> Option<Fit> {
+    let observations: Vec<(f64, f64, f64)> = observations
+        .iter()
+        .filter(|(_, _, samples)| *samples > 0)
+        .map(|&(alpha, satisfiable, samples)| (alpha, satisfiable as f64, samples as f64))
+        .collect();
+    let share = |&(_, satisfiable, samples): &(f64, f64, f64)| satisfiable / samples;
+    if observations.len() < 2 || !observations.iter().any(|o| share(o) > 0.5) || !observations.iter().any(|o| share(o) < 0.5) {
+        return None;
+    }
+
+    // Start from the first crossing of 1/2, with a width of a tenth of the range
+    let crossing = observations.windows(2).find(|pair| share(&pair[0]) >= 0.5 && share(&pair[1]) < 0.5);
+    let alpha_c = crossing.map_or(observations[0].0, |pair| (pair[0].0 + pair[1].0) / 2.);
+    let range = observations.iter().map(|o| o.0).fold(f64::NEG_INFINITY, f64::max)
+        - observations.iter().map(|o| o.0).fold(f64::INFINITY, f64::min);
+    let width = (range / 10.).max(1e-3);
+    let mut beta = [alpha_c / width, -1. / width];
+
+    let log_likelihood = |beta: [f64; 2]| {
+        observations
+            .iter()
+            .map(|&(alpha, satisfiable, samples)| {
+                let p = model.cdf(beta[0] + beta[1] * alpha).0.clamp(1e-300, 1. - 1e-16);
+                satisfiable * p.ln() + (samples - satisfiable) * (1. - p).ln()
+            })
+            .sum::<f64>()
+    };
+    let mut information = [[0.; 2]; 2];
+    let mut converged = false;
+    for _ in 0..200 {
+        // Score (gradient of the log-likelihood) and Fisher information
+        let mut score = [0.; 2];
+        information = [[0.; 2]; 2];
+        for &(alpha, satisfiable, samples) in observations.iter() {
+            let (p, density) = model.cdf(beta[0] + beta[1] * alpha);
+            let p = p.clamp(1e-12, 1. - 1e-12);
+            let x = [1., alpha];
+            let weight = samples * density * density / (p * (1. - p));
+            let residual = (satisfiable - samples * p) * density / (p * (1. - p));
+            for i in 0..2 {
+                score[i] += residual * x[i];
+                for j in 0..2 {
+                    information[i][j] += weight * x[i] * x[j];
+                }
+            }
+        }
+        let determinant = information[0][0] * information[1][1] - information[0][1] * information[1][0];
+        if determinant.abs() < 1e-300 {
+            return None;
+        }
+        let step = [
+            (information[1][1] * score[0] - information[0][1] * score[1]) / determinant,
+            (information[0][0] * score[1] - information[1][0] * score[0]) / determinant,
+        ];
+        // Halve the step until the likelihood does not decrease
+        let current = log_likelihood(beta);
+        let mut scale = 1.;
+        let mut next = [beta[0] + step[0], beta[1] + step[1]];
+        while log_likelihood(next) < current && scale > 1e-6 {
+            scale /= 2.;
+            next = [beta[0] + scale * step[0], beta[1] + scale * step[1]];
+        }
+        beta = next;
+        if (scale * step[0]).abs() < 1e-10 * (1. + beta[0].abs()) && (scale * step[1]).abs() < 1e-10 * (1. + beta[1].abs()) {
+            converged = true;
+            break;
+        }
+    }
+    if !converged || beta[1] >= 0. {
+        return None;
+    }
+
+    // Covariance of the parameters and delta method
+    let determinant = information[0][0] * information[1][1] - information[0][1] * information[1][0];
+    let covariance = [
+        [information[1][1] / determinant, -information[0][1] / determinant],
+        [-information[1][0] / determinant, information[0][0] / determinant],
+    ];
+    let variance = |gradient: [f64; 2]| {
+        (0..2)
+            .flat_map(|i| (0..2).map(move |j| (i, j)))
+            .map(|(i, j)| gradient[i] * covariance[i][j] * gradient[j])
+            .sum::<f64>()
+    };
+    let [b0, b1] = beta;
+    Some(Fit {
+        model,
+        alpha_c: -b0 / b1,
+        alpha_c_error: variance([-1. / b1, b0 / (b1 * b1)]).sqrt(),
+        width: -1. / b1,
+        width_error: variance([0., 1. / (b1 * b1)]).sqrt(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Expected observations of an exact model, with many samples per point
+    fn observations(fit: &Fit, samples: u32) -> Vec<(f64, u32, u32)> {
+        (0..=40)
+            .map(|i| {
+                let alpha = 3. + i as f64 * 0.05;
+                let satisfiable = (fit.probability(alpha) * samples as f64).round() as u32;
+                (alpha, satisfiable, samples)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_fit() {
+        for model in [Model::Logistic, Model::Erf] {
+            let exact = Fit {
+                model,
+                alpha_c: 4.2,
+                alpha_c_error: 0.,
+                width: 0.3,
+                width_error: 0.,
+            };
+            let fitted = fit(model, &observations(&exact, 100_000)).unwrap();
+            assert!((fitted.alpha_c - 4.2).abs() < 1e-3);
+            assert!((fitted.width - 0.3).abs() < 1e-3);
+            assert!(fitted.alpha_c_error > 0. && fitted.alpha_c_error < 1e-2);
+
+            // Errors shrink as the square root of the number of samples
+            let coarse = fit(model, &observations(&exact, 1000)).unwrap();
+            let ratio = coarse.alpha_c_error / fitted.alpha_c_error;
+            assert!((ratio - 10.).abs() < 0.5);
+        }
+    }
+
+    #[test]
+    fn test_no_transition() {
+        assert_eq!(None, fit(Model::Logistic, &[(1., 10, 10), (2., 10, 10)]));
+        assert_eq!(None, fit(Model::Logistic, &[(1., 0, 10), (2., 10, 10)]));
+    }
+}
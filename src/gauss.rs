@@ -0,0 +1,175 @@
+use crate::solver::{Solution, Statistics};
+use crate::xorsat::XorFormula;
+
+
+/// Solver for XOR-SAT problems
+///
+/// The constraints of the formula form a linear system over GF(2), whose
+/// rows are packed in 64-bit words, the parity being stored as an extra
+/// column. Gauss-Jordan elimination brings the system to reduced row
+/// echelon form: it is unsatisfiable if a row reduces to 0 = 1, and
+/// otherwise each assignment of the variables without a pivot extends to a
+/// solution. They are arbitrarily set to false, so that each pivot
+/// variable equals the parity of its row.
+///
+/// The row additions are counted as propagations (each one eliminates a
+/// variable from a constraint), and an inconsistent row as a conflict.
+pub struct GaussSolver;
+
+impl GaussSolver {
+    pub fn name(&self) -> &'static str {
+        "gauss"
+    }
+
+    pub fn solve(&self, formula: &XorFormula) -> Solution {
+        let n = formula.num_variables();
+        let mut rows = pack_rows(formula, n);
+        let mut statistics = Statistics::default();
+
+        let mut pivots = vec![];
+        for column in 0..n {
+            let rank = pivots.len();
+            let Some(pivot) = (rank..rows.len()).find(|&row| bit(&rows[row], column)) else {
+                continue;
+            };
+            rows.swap(rank, pivot);
+            let (above, below) = rows.split_at_mut(rank);
+            let (pivot_row, below) = below.split_first_mut().unwrap();
+            for row in above.iter_mut().chain(below.iter_mut()) {
+                if bit(row, column) {
+                    for (word, pivot_word) in row.iter_mut().zip(pivot_row.iter()) {
+                        *word ^= pivot_word;
+                    }
+                    statistics.propagations += 1;
+                }
+            }
+            pivots.push(column);
+        }
+
+        // The rows after the pivots have no variable left
+        if rows[pivots.len()..].iter().any(|row| bit(row, n)) {
+            statistics.conflicts += 1;
+            return Solution {
+                model: None,
+                statistics,
+            };
+        }
+        // The free variables are false, and the others follow from them
+        let mut model = vec![Some(false); n];
+        for (row, &column) in rows.iter().zip(pivots.iter()) {
+            model[column] = Some(bit(row, n));
+        }
+        Solution {
+            model: Some(model),
+            statistics,
+        }
+    }
+}
+
+/// Rows of the augmented matrix of the system, with the coefficients of
+/// the n variables followed by the parity (a variable occurring twice in a
+/// constraint cancels out)
+fn pack_rows(formula: &XorFormula, n: usize) -> Vec<Vec<u64>> {
+    let words = n / 64 + 1;
+    formula
+        .constraints
+        .iter()
+        .map(|constraint| {
+            let mut row = vec![0; words];
+            for &variable in constraint.variables.iter() {
+                flip(&mut row, variable as usize);
+            }
+            if constraint.parity {
+                flip(&mut row, n);
+            }
+            row
+        })
+        .collect()
+}
+
+#[inline(always)]
+fn bit(row: &[u64], column: usize) -> bool {
+    (row[column / 64] >> (column % 64)) & 1 == 1
+}
+
+#[inline(always)]
+fn flip(row: &mut [u64], column: usize) {
+    row[column / 64] ^= 1 << (column % 64);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cnf::Evaluation;
+    use crate::xorsat::{generate_xor, XorConstraint};
+
+    fn constraint(variables: &[u32], parity: bool) -> XorConstraint {
+        XorConstraint {
+            variables: variables.to_vec(),
+            parity,
+        }
+    }
+
+    /// Satisfiability of a formula by trying every assignment
+    fn brute_force(formula: &XorFormula, n: u32) -> bool {
+        (0..1u32 << n).any(|bits| {
+            let assignment: Vec<Option<bool>> = (0..n).map(|variable| Some(bits >> variable & 1 == 1)).collect();
+            formula.evaluate(&assignment) == Evaluation::Satisfied
+        })
+    }
+
+    #[test]
+    fn test_contradiction() {
+        // x_0⊕x_1 = 1, x_1⊕x_2 = 1 and x_0⊕x_2 = 1 add up to 0 = 1
+        let formula = XorFormula {
+            constraints: vec![constraint(&[0, 1], true), constraint(&[1, 2], true), constraint(&[0, 2], true)],
+        };
+        let solution = GaussSolver.solve(&formula);
+        assert!(!solution.is_sat());
+        assert_eq!(1, solution.statistics.conflicts);
+    }
+
+    #[test]
+    fn test_cancellation() {
+        // x_0⊕x_0⊕x_1 = 1 reduces to x_1 = 1
+        let formula = XorFormula {
+            constraints: vec![constraint(&[0, 0, 1], true)],
+        };
+        assert_eq!(Some(vec![Some(false), Some(true)]), GaussSolver.solve(&formula).model);
+    }
+
+    #[test]
+    fn test_free_variable() {
+        // x_0⊕x_1 = 1 leaves x_1 free, set to false, and x_0 follows
+        let formula = XorFormula {
+            constraints: vec![constraint(&[0, 1], true)],
+        };
+        let model = GaussSolver.solve(&formula).model;
+        assert_eq!(Some(vec![Some(true), Some(false)]), model);
+        assert_eq!(Evaluation::Satisfied, formula.evaluate(&model.unwrap()));
+    }
+
+    #[test]
+    fn test_brute_force() {
+        // Around the threshold of 3-XORSAT, about 0.918, with more than 64
+        // columns in the larger formulas
+        for (n, alpha) in [(8, 0.6), (8, 0.9), (8, 1.2), (70, 0.9)] {
+            for seed in 0..20 {
+                let formula = generate_xor(3, n, alpha, Some(seed));
+                let solution = GaussSolver.solve(&formula);
+                if let Some(model) = &solution.model {
+                    assert_eq!(Evaluation::Satisfied, formula.evaluate(model));
+                }
+                if n <= 16 {
+                    assert_eq!(brute_force(&formula, n), solution.is_sat());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_random() {
+        assert!(GaussSolver.solve(&generate_xor(3, 500, 0.7, Some(42))).is_sat());
+        assert!(!GaussSolver.solve(&generate_xor(3, 500, 1.1, Some(42))).is_sat());
+    }
+}
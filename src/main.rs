@@ -1,37 +1,245 @@
-use clap::Parser;
+use clap::{error::ErrorKind, Args, CommandFactory, Parser, Subcommand, ValueEnum};
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::Instant;
+
+mod adaptive;
+use adaptive::{Estimate, Refinement};
+
+mod cdcl;
+use cdcl::CdclSolver;
 
 mod cnf;
+use cnf::{Assignment, Evaluation};
+
+mod colouring;
+
+mod dimacs;
+use dimacs::{complete_model, parse_dimacs, write_dimacs, write_solution, write_xor_dimacs};
 
 mod digraph;
-use digraph::digraph_2sat;
+use digraph::DigraphSolver;
 
 mod dpll;
-use dpll::dpll_sat;
+use dpll::DpllSolver;
+
+mod fit;
+use fit::{fit, Fit, Model};
+
+mod gauss;
+use gauss::GaussSolver;
+
+mod names;
+use names::value_name;
+
+mod parallel;
+use parallel::parallel_map;
 
 mod plot;
-use plot::plot_series;
+use plot::{plot_cost, plot_series, PlotOptions, Series};
+
+mod problem;
+use problem::{Ensemble, Formula, Problem};
+
+mod propagate;
 
 mod rand_cnf;
-use rand_cnf::generate_cnf;
+use rand_cnf::{sample_seed, Generator};
+
+mod results;
+use results::{SweepConfig, SweepPoint, SweepResults};
+
+mod scaling;
+use scaling::{collapse, scaling_variable, Curve};
+
+mod solver;
+use solver::{Solution, Solver};
+
+mod stats;
+use stats::Interval;
+
+mod xorsat;
 
 
 /// Investigate phase transition in k-SAT problems
 #[derive(Parser)]
-#[command(version, about, long_about = None)]
+#[command(version, about, long_about = None, args_conflicts_with_subcommands = true)]
 struct Cli {
-    /// The number k of literals per clause (e.g. 3 for 3-SAT)
-    #[arg(long, short = 'k', default_value_t = 3)]
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    sweep: SweepArgs,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Solve a single formula in DIMACS format
+    ///
+    /// The result is reported in the SAT competition format, with exit
+    /// code 10 if the formula is satisfiable and 20 otherwise.
+    Solve(SolveArgs),
+
+    /// Regenerate a formula of a sweep and print it in DIMACS format
+    ///
+    /// The formula is the one generated by a sweep run with the same k, n
+    /// and seed, for the given value of alpha (with index `alpha-index`
+    /// among the values of the finest grid of the sweep, as written in the
+    /// `alpha_index` column of its results) and the given sample. XOR-SAT
+    /// formulas are written in the extended DIMACS format of CryptoMiniSat,
+    /// as clauses prefixed with `x`.
+    Generate(GenerateArgs),
+
+    /// Plot the share of satisfiable formulas from results saved with `--output-csv`
+    ///
+    /// The results of several sweeps with the same k and n are merged,
+    /// adding up the samples of equal values of alpha, while results for
+    /// different values of k or n are plotted as separate series.
+    Plot(PlotArgs),
+
+    /// Estimate the threshold and the critical exponent ν by finite-size scaling
+    ///
+    /// The sweeps for several values of n are loaded from CSV files, or run
+    /// with the sweep options if no file is given. The threshold α_c and the
+    /// exponent ν are searched so that the curves collapse onto a single
+    /// master curve when plotted against (α - α_c)·n^(1/ν), which is plotted.
+    Scaling(Box<ScalingArgs>),
+}
+
+#[derive(Args)]
+struct SolveArgs {
+    /// Path to the DIMACS CNF file
+    file: PathBuf,
+
+    /// The solver to use
+    #[arg(long, value_enum, default_value_t = SolverChoice::Auto)]
+    solver: SolverChoice,
+}
+
+#[derive(Args)]
+struct GenerateArgs {
+    /// The kind of constraints of the formula
+    #[arg(long, value_enum, default_value_t = Problem::Sat)]
+    problem: Problem,
+
+    /// The number k of literals per clause (or of colours in graph colouring)
+    #[arg(long, short = 'k', default_value_t = 3, value_parser = clap::value_parser!(u8).range(1..))]
     k: u8,
 
     /// The number n of available variables
     #[arg(long, short = 'n', default_value_t = 25)]
     n: u32,
 
-    /// The number of generated samples per point (s.p.p.)
+    /// The ratio of clauses to variables (or the average degree c in graph colouring)
+    #[arg(long)]
+    alpha: f32,
+
+    /// The distribution of the formulas of the sweep
+    #[arg(long, value_enum, default_value_t = Generator::Uniform)]
+    generator: Generator,
+
+    /// The fraction of clauses of length k in mixed formulas, the others having length 2
+    #[arg(long)]
+    p: Option<f64>,
+
+    /// Break the symmetry between colours in graph colouring
+    #[arg(long)]
+    symmetry_breaking: bool,
+
+    /// The master seed of the sweep
+    #[arg(long)]
+    seed: u64,
+
+    /// The index of alpha on the grid of the sweep (the `alpha_index` column of its results)
+    #[arg(long, default_value_t = 0)]
+    alpha_index: usize,
+
+    /// The index of the sample
+    #[arg(long, default_value_t = 0)]
+    sample: usize,
+}
+
+#[derive(Args)]
+struct PlotArgs {
+    /// CSV files written by `--output-csv`
+    #[arg(required = true, value_name = "FILES")]
+    files: Vec<PathBuf>,
+
+    /// Title of the chart (derived from the configuration of the sweep by default)
+    #[arg(long)]
+    title: Option<String>,
+
+    /// Width of the image, in pixels
+    #[arg(long, default_value_t = 640)]
+    width: u32,
+
+    /// Height of the image, in pixels
+    #[arg(long, default_value_t = 480)]
+    height: u32,
+
+    /// Lower bound of the range of alpha (the smallest value of the results by default)
+    #[arg(long)]
+    alpha_start: Option<f32>,
+
+    /// Upper bound of the range of alpha (the largest value of the results by default)
+    #[arg(long)]
+    alpha_end: Option<f32>,
+
+    /// Path of the PNG file (derived from the title by default)
+    #[arg(long, short = 'o', value_name = "FILE")]
+    output: Option<PathBuf>,
+
+    /// Fit a model of the transition to each series and report its threshold and width
+    #[arg(long, value_enum, value_name = "MODEL")]
+    fit: Option<Model>,
+}
+
+#[derive(Args)]
+struct ScalingArgs {
+    /// CSV files written by `--output-csv`, for a single k and several values of n
+    #[arg(value_name = "FILES", conflicts_with = "SweepArgs")]
+    files: Vec<PathBuf>,
+
+    /// Title of the chart (derived from the configuration of the sweeps by default)
+    #[arg(long)]
+    title: Option<String>,
+
+    /// Path of the PNG file (derived from the title by default)
+    #[arg(long, short = 'o', value_name = "FILE")]
+    output: Option<PathBuf>,
+
+    #[command(flatten)]
+    sweep: SweepArgs,
+}
+
+/// Sample random formulas for a range of values of alpha (default command)
+#[derive(Args)]
+struct SweepArgs {
+    /// The kind of constraints of the formulas, or a comma-separated list
+    /// of problems plotted as separate series
+    #[arg(long, value_enum, default_value = "sat", value_delimiter = ',')]
+    problem: Vec<Problem>,
+
+    /// The number k of literals per clause (e.g. 3 for 3-SAT) or of colours
+    /// in graph colouring, or a comma-separated list of values plotted as
+    /// separate series
+    #[arg(long, short = 'k', default_value = "3", value_delimiter = ',', value_parser = clap::value_parser!(u8).range(1..))]
+    k: Vec<u8>,
+
+    /// The number n of available variables, or a comma-separated list of values
+    /// plotted as separate series
+    #[arg(long, short = 'n', default_value = "25", value_delimiter = ',')]
+    n: Vec<u32>,
+
+    /// The number of generated samples per point (s.p.p.), or the initial
+    /// number of samples per point in adaptive mode
     #[arg(long, short = 's', default_value_t = 100)]
     samples: u32,
 
-    /// Lower bound for values of alpha
+    /// Lower bound for values of alpha (the average degree c in graph colouring)
     #[arg(long, default_value_t = 0.)]
     alpha_start: f32,
 
@@ -43,44 +251,1312 @@ struct Cli {
     #[arg(long, default_value_t = 100)]
     alpha_steps: usize,
 
+    /// The distribution of the random formulas
+    #[arg(long, value_enum, default_value_t = Generator::Uniform)]
+    generator: Generator,
+
+    /// Fraction p of clauses of length k in mixed formulas, the others having
+    /// length 2 as in (2+p)-SAT, or a comma-separated list of values plotted
+    /// as separate series
+    #[arg(long, value_delimiter = ',')]
+    p: Vec<f64>,
+
+    /// Sweep p from 0 to 1 (in `alpha-steps` steps) at the given ratio of
+    /// clauses to variables, rather than alpha
+    #[arg(long, value_name = "ALPHA", conflicts_with_all = ["p", "alpha_start", "alpha_end"])]
+    vary_p: Option<f32>,
+
+    /// Break the symmetry between colours in graph colouring, by restricting
+    /// the i-th vertex of largest degree to the first i colours
+    #[arg(long)]
+    symmetry_breaking: bool,
+
     /// Verbosity (when turned on, the computed values are displayed)
     #[arg(long)]
     verbose: bool,
+
+    /// Check every satisfying assignment found against its formula, and that
+    /// planted formulas are found satisfiable (the sweep is aborted if a
+    /// solver returns an invalid answer)
+    #[arg(long)]
+    check: bool,
+
+    /// Directory where every generated formula is written in DIMACS format
+    #[arg(long, value_name = "DIR")]
+    dump_dimacs: Option<PathBuf>,
+
+    /// The solver of the CNF formulas (XOR-SAT formulas are always solved by
+    /// Gaussian elimination)
+    #[arg(long, value_enum, default_value_t = SolverChoice::Auto)]
+    solver: SolverChoice,
+
+    /// Also plot the median and mean cost of the solver against alpha
+    #[arg(long, value_enum)]
+    cost: Option<CostMetric>,
+
+    /// Overlay the share of satisfiable formulas on the cost plot
+    #[arg(long, requires = "cost")]
+    overlay: bool,
+
+    /// Number of worker threads solving the samples of each value of alpha
+    /// (the results do not depend on it)
+    #[arg(long, short = 'j', default_value_t = 1)]
+    jobs: usize,
+
+    /// Master seed from which the formulas are generated (a random seed is
+    /// used by default, in both cases it is printed to make the sweep reproducible)
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Fit a model of the transition to each series and report its threshold and width
+    #[arg(long, value_enum, value_name = "MODEL")]
+    fit: Option<Model>,
+
+    /// Method computing the confidence intervals of the shares of satisfiable formulas
+    #[arg(long, value_enum, default_value_t = Interval::Wilson)]
+    interval: Interval,
+
+    /// Confidence level of the intervals
+    #[arg(long, default_value_t = 0.95)]
+    confidence: f64,
+
+    /// Concentrate the samples near the transition: starting from the grid
+    /// of alpha, add samples in the transition where the confidence
+    /// intervals are wider than the target precision, and refine the grid
+    /// where the share of satisfiable formulas lies between 0.05 and 0.95
+    #[arg(long)]
+    adaptive: bool,
+
+    /// Target half-width of the confidence intervals in adaptive mode
+    #[arg(long, default_value_t = 0.02, requires = "adaptive")]
+    precision: f64,
+
+    /// Maximum number of samples per point in adaptive mode
+    #[arg(long, default_value_t = 10000, requires = "adaptive")]
+    max_samples: u32,
+
+    /// Maximum number of times the intervals of the grid of alpha are halved in adaptive mode
+    #[arg(long, default_value_t = 4, requires = "adaptive", value_parser = clap::value_parser!(u32).range(0..=16))]
+    refine: u32,
+
+    /// File where the results and the configuration of the sweep are written in CSV format
+    #[arg(long, value_name = "FILE")]
+    output_csv: Option<PathBuf>,
+
+    /// File where the results and the configuration of the sweep are written in JSON format
+    #[arg(long, value_name = "FILE")]
+    output_json: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum CostMetric {
+    /// Number of branching decisions
+    Decisions,
+    /// Number of literals implied by unit propagation
+    Propagations,
+    /// Number of conflicts
+    Conflicts,
+    /// Wall-clock time of the solver
+    Time,
+}
+
+impl CostMetric {
+    fn measure(self, solution: &Solution, seconds: f64) -> f64 {
+        match self {
+            CostMetric::Decisions => solution.statistics.decisions as f64,
+            CostMetric::Propagations => solution.statistics.propagations as f64,
+            CostMetric::Conflicts => solution.statistics.conflicts as f64,
+            CostMetric::Time => seconds,
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            CostMetric::Decisions => "Decisions",
+            CostMetric::Propagations => "Propagations",
+            CostMetric::Conflicts => "Conflicts",
+            CostMetric::Time => "Time (s)",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum SolverChoice {
+    /// Implication graph for 2-SAT formulas, DPLL otherwise
+    Auto,
+    /// DPLL with unit propagation and pure literal elimination
+    Dpll,
+    /// Conflict-driven clause learning
+    Cdcl,
+    /// Strongly connected components of the implication graph (2-SAT only)
+    #[value(name = "2sat")]
+    TwoSat,
+}
+
+impl SolverChoice {
+    fn solver(self, two_sat: bool) -> &'static dyn Solver {
+        match self {
+            SolverChoice::Auto if two_sat => &DigraphSolver,
+            SolverChoice::Auto | SolverChoice::Dpll => &DpllSolver,
+            SolverChoice::Cdcl => &CdclSolver,
+            SolverChoice::TwoSat => &DigraphSolver,
+        }
+    }
+}
+
+fn check_model(formula: &Formula, model: &Assignment) -> Result<(), String> {
+    if formula.evaluate(model) != Evaluation::Satisfied {
+        return Err(format!("The solver returned an invalid model {:?} for formula {}", model, formula));
+    }
+    Ok(())
+}
+
+/// Model printed by the `solve` subcommand, completed as by `write_solution`
+/// and checked against the formula
+fn solution_model(formula: Formula, model: &Assignment, variables: u32) -> Result<Assignment, String> {
+    let model = complete_model(model, variables);
+    check_model(&formula, &model)?;
+    Ok(model)
+}
+
+
+impl GenerateArgs {
+    fn ensemble(&self) -> Ensemble {
+        Ensemble {
+            problem: self.problem,
+            k: self.k,
+            n: self.n,
+            alpha: self.alpha,
+            generator: self.generator,
+            p: self.p,
+            symmetry_breaking: self.symmetry_breaking,
+        }
+    }
+}
+
+/// Name of the file where a formula of a sweep is dumped, with the values of
+/// alpha and p at full precision so that the points of a fine grid differ
+fn dump_name(ensemble: &Ensemble, sample: usize) -> String {
+    let (problem, k, n) = (ensemble.problem, ensemble.k, ensemble.n);
+    let p = ensemble.p.map(|p| format!("-p-{}", p)).unwrap_or_default();
+    let parameter = if problem == Problem::Colouring { "c" } else { "alpha" };
+    let generator = value_name(&ensemble.generator);
+    let alpha = format!("{}-{}", parameter, ensemble.alpha);
+    format!("{}-{}-{}-n-{}{}-{}-{}.cnf", k, value_name(&problem), generator, n, p, alpha, sample)
+}
+
+/// Command line of the `generate` subcommand regenerating a formula of a sweep
+fn generate_command(ensemble: &Ensemble, seed: u64, alpha_index: usize, sample: usize) -> String {
+    let generator = match ensemble.generator {
+        Generator::Uniform => String::new(),
+        generator => format!(" --generator {}", value_name(&generator)),
+    };
+    let problem = match ensemble.problem {
+        Problem::Sat => String::new(),
+        problem => format!(" --problem {}", value_name(&problem)),
+    };
+    let p = ensemble.p.map(|p| format!(" --p {}", p)).unwrap_or_default();
+    let symmetry_breaking = if ensemble.symmetry_breaking { " --symmetry-breaking" } else { "" };
+    format!(
+        "satisfaction generate{} -k {} -n {} --alpha {}{}{}{} --seed {} --alpha-index {} --sample {}",
+        problem,
+        ensemble.k,
+        ensemble.n,
+        ensemble.alpha,
+        generator,
+        p,
+        symmetry_breaking,
+        seed,
+        alpha_index,
+        sample
+    )
+}
+
+/// Comments of a formula of a sweep: the command regenerating it, and the
+/// hidden assignment of a planted formula as DIMACS literals
+fn formula_comments(command: String, planted: Option<&[bool]>) -> Vec<String> {
+    let planted = planted.map(|planted| {
+        let literals: String = planted
+            .iter()
+            .enumerate()
+            .map(|(variable, &value)| format!("{}{} ", if value { "" } else { "-" }, variable + 1))
+            .collect();
+        format!("planted assignment: {}0", literals)
+    });
+    std::iter::once(command).chain(planted).collect()
+}
+
+/// Name of a sweep in the messages, e.g. "3-SAT, N=100", "3-SAT planted,
+/// N=100", "3-SAT, p=0.4, N=100" or "3-XORSAT, N=100"
+fn sweep_name(config: &SweepConfig) -> String {
+    let mut name = config.problem.label(&config.k.to_string());
+    if config.generator != Generator::Uniform {
+        name += &format!(" {}", value_name(&config.generator));
+    }
+    if let Some(p) = config.p {
+        name += &format!(", p={}", p);
+    }
+    if let Some(alpha) = config.alpha {
+        name += &format!(", alpha={}", alpha);
+    }
+    format!("{}, N={}", name, config.n)
+}
+
+/// Title of a chart of sweeps, listing their problems and their values of
+/// k, p, alpha (when p is swept) and n
+fn chart_title(results: &[SweepResults]) -> String {
+    let mut problems: Vec<Problem> = vec![];
+    let mut ks: Vec<u8> = vec![];
+    let mut ns: Vec<u32> = vec![];
+    let mut ps: Vec<f64> = vec![];
+    let mut alphas: Vec<f32> = vec![];
+    for config in results.iter().map(|results| &results.config) {
+        if !problems.contains(&config.problem) {
+            problems.push(config.problem);
+        }
+        if !ks.contains(&config.k) {
+            ks.push(config.k);
+        }
+        if !ns.contains(&config.n) {
+            ns.push(config.n);
+        }
+        if let Some(p) = config.p.filter(|p| !ps.contains(p)) {
+            ps.push(p);
+        }
+        if let Some(alpha) = config.alpha.filter(|alpha| !alphas.contains(alpha)) {
+            alphas.push(alpha);
+        }
+    }
+    let join = |values: Vec<String>| values.join(",");
+    // Adaptive sweeps have a varying number of samples per point
+    let samples: Vec<u32> = results
+        .iter()
+        .flat_map(|results| results.points.iter().map(|point| point.samples))
+        .collect();
+    let (min, max) = (samples.iter().min().copied().unwrap_or(0), samples.iter().max().copied().unwrap_or(0));
+    let samples = if min == max {
+        min.to_string()
+    } else {
+        format!("{}-{}", min, max)
+    };
+    // The generator is given when all sweeps share one other than uniform
+    let generator = match results.first().map(|results| results.config.generator) {
+        Some(generator)
+            if generator != Generator::Uniform
+                && results.iter().all(|results| results.config.generator == generator) =>
+        {
+            format!(" {}", value_name(&generator))
+        }
+        _ => String::new(),
+    };
+    let mut parameters = String::new();
+    if !ps.is_empty() {
+        parameters += &format!(", p={}", join(ps.iter().map(f64::to_string).collect()));
+    }
+    if !alphas.is_empty() {
+        parameters += &format!(", α={}", join(alphas.iter().map(f32::to_string).collect()));
+    }
+    let ks = join(ks.iter().map(u8::to_string).collect());
+    format!(
+        "{}{}{}, N={} ({} s.p.p.)",
+        problems.iter().map(|problem| problem.label(&ks)).collect::<Vec<_>>().join("/"),
+        generator,
+        parameters,
+        join(ns.iter().map(u32::to_string).collect()),
+        samples
+    )
+}
+
+/// Label of the series of a sweep in a chart of several sweeps, showing
+/// the parameters which differ between the sweeps
+fn series_label(config: &SweepConfig, all: &[SweepResults]) -> String {
+    let vary = |same: fn(&SweepConfig, &SweepConfig) -> bool| all.iter().any(|results| !same(&results.config, config));
+    let mut parts = vec![];
+    if vary(|a, b| a.problem == b.problem) {
+        parts.push(config.problem.label(&config.k.to_string()));
+    } else if vary(|a, b| a.k == b.k) {
+        parts.push(format!("k={}", config.k));
+    }
+    if let Some(p) = config.p.filter(|_| vary(|a, b| a.p == b.p)) {
+        parts.push(format!("p={}", p));
+    }
+    if let Some(alpha) = config.alpha.filter(|_| vary(|a, b| a.alpha == b.alpha)) {
+        parts.push(format!("α={}", alpha));
+    }
+    if parts.is_empty() || vary(|a, b| a.n == b.n) {
+        parts.push(format!("N={}", config.n));
+    }
+    if vary(|a, b| a.generator == b.generator) {
+        parts.push(value_name(&config.generator));
+    }
+    parts.join(", ")
+}
+
+/// Options of the charts of sweeps, whose horizontal axis is alpha, p or c
+fn plot_options(title: String, all: &[SweepResults]) -> PlotOptions {
+    let options = PlotOptions::new(title);
+    match all.first().map(|results| results.config.parameter()) {
+        Some("c") => PlotOptions {
+            x_desc: "c (average degree)".to_string(),
+            threshold: "c_c".to_string(),
+            ..options
+        },
+        Some("p") => PlotOptions {
+            x_desc: "p (fraction of clauses of length k)".to_string(),
+            threshold: "p_c".to_string(),
+            ..options
+        },
+        _ => options,
+    }
+}
+
+/// Range of the parameter of a sweep: alpha, or p when alpha is fixed
+fn parameter_range(cli: &SweepArgs) -> RangeInclusive<f32> {
+    match cli.vary_p {
+        Some(_) => 0. ..=1.,
+        None => cli.alpha_start..=cli.alpha_end,
+    }
+}
+
+fn series<T>(all: &[SweepResults], value: impl Fn(&SweepPoint) -> Option<T>) -> Vec<Series<T>> {
+    all.iter()
+        .map(|results| {
+            let (alphas, values) = results
+                .points
+                .iter()
+                .filter_map(|point| value(point).map(|value| (point.alpha, value)))
+                .unzip();
+            Series {
+                label: series_label(&results.config, all),
+                alphas,
+                values,
+                bounds: None,
+            }
+        })
+        .collect()
+}
+
+/// Series of the shares of satisfiable formulas of sweeps for the values
+/// of alpha in a range, along with their confidence intervals
+fn probability_series(all: &[SweepResults], range: RangeInclusive<f32>) -> Vec<Series<f32>> {
+    let mut series = series(all, |point| range.contains(&point.alpha).then_some(point.probability() as f32));
+    for (series, results) in series.iter_mut().zip(all) {
+        let bounds = results
+            .points
+            .iter()
+            .filter(|point| range.contains(&point.alpha))
+            .map(|point| (point.lower as f32, point.upper as f32))
+            .collect();
+        series.bounds = Some(bounds);
+    }
+    series
+}
+
+/// Path of the output file of a sweep, suffixed with its values of k, its
+/// problem, p (if mixed) and n if several sweeps were run
+fn output_path(path: &Path, config: &SweepConfig, several: bool) -> PathBuf {
+    if !several {
+        return path.to_path_buf();
+    }
+    let stem = path.file_stem().map(|stem| stem.to_string_lossy()).unwrap_or_default();
+    let p = config.p.map(|p| format!("-p-{}", p)).unwrap_or_default();
+    let mut name = format!("{}-{}-{}{}-n-{}", stem, config.k, value_name(&config.problem), p, config.n);
+    if let Some(extension) = path.extension() {
+        name = format!("{}.{}", name, extension.to_string_lossy());
+    }
+    path.with_file_name(name)
+}
+
+/// Fit a model of the transition to the results of each sweep, printing
+/// the fitted thresholds and widths
+///
+/// The share of satisfiable formulas increases with p: sweeps over p are
+/// fitted on the opposite axis, and their fit is mirrored back, with a
+/// negative width.
+fn fit_transitions(all: &[SweepResults], model: Option<Model>) -> Vec<Option<Fit>> {
+    let Some(model) = model else {
+        return vec![];
+    };
+    all.iter()
+        .map(|results| {
+            let sign = if results.config.parameter() == "p" { -1. } else { 1. };
+            let mut observations: Vec<(f64, u32, u32)> = results
+                .points
+                .iter()
+                .map(|point| (sign * point.alpha as f64, point.satisfiable, point.samples))
+                .collect();
+            observations.sort_by(|a, b| a.0.total_cmp(&b.0));
+            let fitted = fit(model, &observations).map(|fit| Fit {
+                alpha_c: sign * fit.alpha_c,
+                width: sign * fit.width,
+                ..fit
+            });
+            let label = sweep_name(&results.config);
+            match &fitted {
+                Some(fit) => println!(
+                    "{}: {}_c = {:.4} ± {:.4}, width = {:.4} ± {:.4}",
+                    label,
+                    results.config.parameter(),
+                    fit.alpha_c,
+                    fit.alpha_c_error,
+                    fit.width.abs(),
+                    fit.width_error
+                ),
+                None => println!("{}: no transition could be fitted", label),
+            }
+            fitted
+        })
+        .collect()
+}
+
+fn write_results(path: &Path, results: &SweepResults, json: bool) {
+    if let Err(err) = File::create(path).map(BufWriter::new).and_then(|mut writer| {
+        if json {
+            results.write_json(&mut writer)?;
+        } else {
+            results.write_csv(&mut writer)?;
+        }
+        writer.flush()
+    }) {
+        panic!("An error occurred while writing {}: {:?}", path.display(), err);
+    }
+    println!("Generated file {}", path.display());
+}
+
+/// Write a formula in DIMACS format, preceded by comment lines
+fn write_formula(writer: &mut impl Write, comments: &[String], formula: &Formula, variables: u32) -> io::Result<()> {
+    for comment in comments.iter() {
+        writeln!(writer, "c {}", comment)?;
+    }
+    match formula {
+        Formula::Cnf(formula) => write_dimacs(writer, formula, variables),
+        Formula::Xor(formula) => write_xor_dimacs(writer, formula, variables),
+    }
+}
+
+fn dump_formula(dir: &Path, name: String, comments: &[String], formula: &Formula, variables: u32) {
+    let path = dir.join(name);
+    if let Err(err) = File::create(&path)
+        .map(BufWriter::new)
+        .and_then(|mut writer| write_formula(&mut writer, comments, formula, variables))
+    {
+        panic!("An error occurred while writing {}: {:?}", path.display(), err);
+    }
 }
 
 
 fn main() {
     let cli = Cli::parse();
+    match cli.command {
+        Some(Command::Solve(args)) => solve(args),
+        Some(Command::Generate(args)) => generate(args),
+        Some(Command::Plot(args)) => plot(args),
+        Some(Command::Scaling(args)) => scaling(*args),
+        None => sweep(cli.sweep),
+    }
+}
+
+
+fn solve(args: SolveArgs) {
+    let formula = fs::read_to_string(&args.file)
+        .map_err(|err| err.to_string())
+        .and_then(|input| parse_dimacs(&input).map_err(|err| err.to_string()));
+    let (formula, variables) = match formula {
+        Ok(result) => result,
+        Err(err) => {
+            eprintln!("Could not read {}: {}", args.file.display(), err);
+            process::exit(1);
+        }
+    };
+
+    let two_sat = formula.clauses.iter().all(|clause| clause.literals.len() == 2);
+    if args.solver == SolverChoice::TwoSat && !two_sat {
+        eprintln!("The 2sat solver requires every clause to have two literals");
+        process::exit(1);
+    }
+    let solver = args.solver.solver(two_sat);
+    println!("c solver: {}", solver.name());
+    let solution = solver.solve(&formula);
+    println!("c decisions: {}", solution.statistics.decisions);
+    println!("c propagations: {}", solution.statistics.propagations);
+    println!("c conflicts: {}", solution.statistics.conflicts);
+    println!("c pure literals: {}", solution.statistics.pure_literals);
+    println!("c backtracks: {}", solution.statistics.backtracks);
+    println!("c max depth: {}", solution.statistics.max_depth);
+    let model = match solution.model {
+        Some(model) => match solution_model(Formula::Cnf(formula), &model, variables) {
+            Ok(model) => Some(model),
+            Err(err) => {
+                eprintln!("{}", err);
+                process::exit(1);
+            }
+        },
+        None => None,
+    };
+    if let Err(err) = write_solution(&mut io::stdout().lock(), model.as_ref(), variables) {
+        panic!("An error occurred while writing the solution: {:?}", err);
+    }
+    process::exit(if model.is_some() { 10 } else { 20 });
+}
+
+
+/// Check that a formula of a problem can have n variables: at least k for
+/// the clauses of k distinct variables, or at least one vertex for graph
+/// colouring, where k is the number of colours
+fn validate_n(problem: Problem, k: u8, n: u32) -> Result<(), &'static str> {
+    match problem {
+        Problem::Colouring if n < 1 => Err("graph colouring requires n >= 1"),
+        Problem::Colouring => Ok(()),
+        _ if n < k.into() => Err("n must be at least k"),
+        _ => Ok(()),
+    }
+}
+
+fn generate(args: GenerateArgs) {
+    if let Err(message) = validate_n(args.problem, args.k, args.n) {
+        Cli::command().error(ErrorKind::ValueValidation, message).exit();
+    }
+    if args.p.is_some() && args.n < 2 {
+        Cli::command()
+            .error(ErrorKind::ValueValidation, "mixed formulas have clauses of length 2, n must be at least 2")
+            .exit();
+    }
+    if args.generator == Generator::QuietPlanted && args.k < 2 {
+        Cli::command()
+            .error(ErrorKind::ValueValidation, "quiet planting requires k >= 2")
+            .exit();
+    }
+    if args.p.is_some_and(|p| !(0. ..=1.).contains(&p)) {
+        Cli::command()
+            .error(ErrorKind::ValueValidation, "p must be between 0 and 1")
+            .exit();
+    }
+    if matches!(args.problem, Problem::XorSat | Problem::Colouring)
+        && (args.generator != Generator::Uniform || args.p.is_some())
+    {
+        Cli::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "XOR-SAT and graph colouring formulas are only generated uniformly, without p",
+            )
+            .exit();
+    }
+    if args.problem != Problem::Sat && matches!(args.generator, Generator::Planted | Generator::QuietPlanted) {
+        Cli::command()
+            .error(ErrorKind::ArgumentConflict, "planted formulas are only generated for k-SAT")
+            .exit();
+    }
+    let ensemble = args.ensemble();
+    let (formula, planted) = ensemble.sample(sample_seed(args.seed, args.alpha_index, args.sample));
+    let command = generate_command(&ensemble, args.seed, args.alpha_index, args.sample);
+    let comments = formula_comments(command, planted.as_deref());
+    if let Err(err) = write_formula(&mut io::stdout().lock(), &comments, &formula, args.n) {
+        panic!("An error occurred while writing the formula: {:?}", err);
+    }
+}
 
-    let alphas: Vec<f32> = (0..=cli.alpha_steps)
-        .map(|i|
-            cli.alpha_start + (cli.alpha_end - cli.alpha_start) * (i as f32) / cli.alpha_steps as f32)
+
+/// Read results saved with `--output-csv`, merging those for the same k and n
+fn load_results(files: &[PathBuf]) -> Vec<SweepResults> {
+    let mut groups: Vec<Vec<SweepResults>> = vec![];
+    for file in files.iter() {
+        let parsed = fs::read_to_string(file)
+            .map_err(|err| err.to_string())
+            .and_then(|input| SweepResults::parse_csv(&input).map_err(|err| err.to_string()));
+        let parsed = match parsed {
+            Ok(parsed) if parsed.points.is_empty() => {
+                eprintln!("Could not read {}: no results", file.display());
+                process::exit(1);
+            }
+            Ok(parsed) => parsed,
+            Err(err) => {
+                eprintln!("Could not read {}: {}", file.display(), err);
+                process::exit(1);
+            }
+        };
+        let key = |config: &SweepConfig| (config.problem, config.k, config.n, config.generator, config.p, config.alpha);
+        match groups.iter_mut().find(|group| key(&group[0].config) == key(&parsed.config)) {
+            Some(group) => group.push(parsed),
+            None => groups.push(vec![parsed]),
+        }
+    }
+    match groups.into_iter().map(SweepResults::merge).collect::<Result<Vec<_>, _>>() {
+        Ok(results) => results,
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(1);
+        }
+    }
+}
+
+
+fn plot(args: PlotArgs) {
+    // Results for the same k and n are merged into a single series
+    let mut results = load_results(&args.files);
+    if results.iter().any(|sweep| sweep.config.parameter() != results[0].config.parameter()) {
+        eprintln!("Cannot plot sweeps over different parameters on the same chart");
+        process::exit(1);
+    }
+    let title = args.title.unwrap_or_else(|| chart_title(&results));
+    let alphas = results.iter().flat_map(|results| results.points.iter().map(|point| point.alpha));
+    let alpha_start = args.alpha_start.unwrap_or_else(|| alphas.clone().fold(f32::INFINITY, f32::min));
+    let alpha_end = args.alpha_end.unwrap_or_else(|| alphas.fold(f32::NEG_INFINITY, f32::max));
+    if alpha_start >= alpha_end {
+        Cli::command()
+            .error(ErrorKind::ValueValidation, "the range of alpha is empty")
+            .exit();
+    }
+    let options = PlotOptions {
+        size: (args.width, args.height),
+        alpha_range: Some(alpha_start..alpha_end),
+        path: args.output,
+        ..plot_options(title, &results)
+    };
+    // The transitions are only fitted to the plotted points
+    for sweep in results.iter_mut() {
+        sweep.points.retain(|point| (alpha_start..=alpha_end).contains(&point.alpha));
+    }
+    let fits = fit_transitions(&results, args.fit);
+    match plot_series(&options, &probability_series(&results, alpha_start..=alpha_end), &fits) {
+        Ok(path) => println!("Generated file {}", path),
+        Err(err) => panic!("An error occurred while generating the plot: {:?}", err),
+    }
+}
+
+
+fn scaling(args: ScalingArgs) {
+    let mut results = if args.files.is_empty() {
+        run_sweeps(&args.sweep)
+    } else {
+        load_results(&args.files)
+    };
+    let key = |sweep: &SweepResults| {
+        let config = &sweep.config;
+        (config.problem, config.k, config.generator, config.p, config.alpha)
+    };
+    if results.len() < 2 || results.iter().any(|sweep| key(sweep) != key(&results[0])) {
+        eprintln!("Finite-size scaling requires sweeps for a single problem, k, generator and p, and several values of n");
+        process::exit(1);
+    }
+    results.sort_by_key(|results| results.config.n);
+
+    // Standard errors of the shares are derived from their confidence intervals
+    let curves: Vec<Curve> = results
+        .iter()
+        .map(|results| {
+            let z = stats::normal_quantile(0.5 + results.config.confidence / 2.);
+            Curve {
+                n: results.config.n,
+                points: results
+                    .points
+                    .iter()
+                    .map(|point| (point.alpha as f64, point.probability(), (point.upper - point.lower) / (2. * z)))
+                    .collect(),
+            }
+        })
         .collect();
-    let values = alphas
+    let Some(collapse) = collapse(&curves) else {
+        eprintln!("Could not collapse the curves: no transition in the results");
+        process::exit(1);
+    };
+    let title = chart_title(&results);
+    println!(
+        "{}: {}_c = {:.4} ± {:.4}, nu = {:.3} ± {:.3} (S = {:.3})",
+        title,
+        results[0].config.parameter(),
+        collapse.alpha_c,
+        collapse.alpha_c_error,
+        collapse.nu,
+        collapse.nu_error,
+        collapse.quality
+    );
+
+    // The chart is centered on the transition, where the scaling variable is
+    // bounded, with as much room on each side (the scaling variable is
+    // computed in f64, and points which overflow are left out)
+    let rescale = |alpha: f32, n: u32| scaling_variable(alpha as f64, collapse.alpha_c, collapse.nu, n);
+    let transition = results.iter().flat_map(|results| {
+        results
+            .points
+            .iter()
+            .filter(|point| (0.05..=0.95).contains(&point.probability()))
+            .map(|point| rescale(point.alpha, results.config.n))
+            .filter(|x| x.is_finite())
+    });
+    let low = transition.clone().fold(f64::INFINITY, f64::min);
+    let high = transition.fold(f64::NEG_INFINITY, f64::max);
+    let margin = ((high - low) / 2.).max(1.);
+    let x_range = (low - margin) as f32..(high + margin) as f32;
+    if !(x_range.start.is_finite() && x_range.end.is_finite()) {
+        eprintln!("Could not plot the collapse: the scaling variable overflows");
+        process::exit(1);
+    }
+    let series: Vec<Series<f32>> = results
         .iter()
-        .map(|alpha| {
-            if cli.verbose {
-                println!("alpha = {}", alpha);
+        .map(|sweep| {
+            let points: Vec<(f32, &SweepPoint)> = sweep
+                .points
+                .iter()
+                .map(|point| (rescale(point.alpha, sweep.config.n) as f32, point))
+                .filter(|(x, _)| x_range.contains(x))
+                .collect();
+            Series {
+                label: series_label(&sweep.config, &results),
+                alphas: points.iter().map(|&(x, _)| x).collect(),
+                values: points.iter().map(|(_, point)| point.probability() as f32).collect(),
+                bounds: Some(points.iter().map(|(_, point)| (point.lower as f32, point.upper as f32)).collect()),
             }
-            (0..cli.samples)
-                .filter(|_| {
-                    let cnf = generate_cnf(cli.k, cli.n, *alpha, None);
-                    if cli.k == 2 {
-                        digraph_2sat(&cnf)
-                    } else {
-                        dpll_sat(&cnf)
-                    }
-                })
-                .count() as f32
-                / cli.samples as f32
         })
         .collect();
-    if cli.verbose {
-        println!("alphas: {:?}", alphas);
-        println!("values: {:?}", values);
+    let parameter = match results[0].config.parameter() {
+        "alpha" => "α",
+        parameter => parameter,
+    };
+    let options = PlotOptions {
+        alpha_range: Some(x_range),
+        x_desc: format!("({0} - {0}_c)·N^(1/ν) with {0}_c = {1:.3}, ν = {2:.3}", parameter, collapse.alpha_c, collapse.nu),
+        path: args.output,
+        ..PlotOptions::new(args.title.unwrap_or_else(|| format!("{} - scaling", title)))
+    };
+    match plot_series(&options, &series, &[]) {
+        Ok(path) => println!("Generated file {}", path),
+        Err(err) => panic!("An error occurred while generating the plot: {:?}", err),
+    }
+}
+
+
+/// Run the sweeps for every value of k and n, and write their results
+fn run_sweeps(cli: &SweepArgs) -> Vec<SweepResults> {
+    // XOR-SAT formulas are solved by Gaussian elimination whatever the solver
+    let cnf = cli.problem.iter().any(|&problem| problem != Problem::XorSat);
+    if cli.solver == SolverChoice::TwoSat && cnf && cli.k.iter().any(|&k| k != 2) {
+        Cli::command()
+            .error(ErrorKind::ArgumentConflict, "the 2sat solver requires k = 2")
+            .exit();
+    }
+    if !(cli.confidence > 0. && cli.confidence < 1.) {
+        Cli::command()
+            .error(ErrorKind::ValueValidation, "the confidence level must be between 0 and 1")
+            .exit();
+    }
+    let mut valid = cli
+        .problem
+        .iter()
+        .flat_map(|&problem| cli.k.iter().flat_map(move |&k| cli.n.iter().map(move |&n| validate_n(problem, k, n))));
+    if let Some(Err(message)) = valid.find(Result::is_err) {
+        Cli::command().error(ErrorKind::ValueValidation, message).exit();
     }
-    match plot_series(format!("{}-SAT, N={} ({} s.p.p.)", cli.k, cli.n, cli.samples), alphas, values) {
+    if (!cli.p.is_empty() || cli.vary_p.is_some()) && cli.n.iter().any(|&n| n < 2) {
+        Cli::command()
+            .error(ErrorKind::ValueValidation, "mixed formulas have clauses of length 2, n must be at least 2")
+            .exit();
+    }
+    if cli.p.iter().any(|p| !(0. ..=1.).contains(p)) {
+        Cli::command()
+            .error(ErrorKind::ValueValidation, "p must be between 0 and 1")
+            .exit();
+    }
+    if cli.problem.iter().any(|problem| matches!(problem, Problem::XorSat | Problem::Colouring))
+        && (cli.generator != Generator::Uniform || !cli.p.is_empty() || cli.vary_p.is_some())
+    {
+        Cli::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "XOR-SAT and graph colouring formulas are only generated uniformly, without p",
+            )
+            .exit();
+    }
+    if cli.problem.contains(&Problem::Colouring) && cli.problem.len() > 1 {
+        Cli::command()
+            .error(ErrorKind::ArgumentConflict, "graph colouring sweeps the average degree, not alpha like the other problems")
+            .exit();
+    }
+    if cli.solver == SolverChoice::TwoSat && cli.symmetry_breaking && cli.problem.contains(&Problem::Colouring) {
+        Cli::command()
+            .error(ErrorKind::ArgumentConflict, "the 2sat solver does not support symmetry breaking")
+            .exit();
+    }
+    if cli.problem.iter().any(|&problem| problem != Problem::Sat)
+        && matches!(cli.generator, Generator::Planted | Generator::QuietPlanted)
+    {
+        Cli::command()
+            .error(ErrorKind::ArgumentConflict, "planted formulas are only generated for k-SAT")
+            .exit();
+    }
+    if cli.generator == Generator::QuietPlanted && cli.k.iter().any(|&k| k < 2) {
+        Cli::command()
+            .error(ErrorKind::ValueValidation, "quiet planting requires k >= 2")
+            .exit();
+    }
+
+    if let Some(dir) = &cli.dump_dimacs {
+        if let Err(err) = fs::create_dir_all(dir) {
+            panic!("An error occurred while creating {}: {:?}", dir.display(), err);
+        }
+    }
+
+    // Every sample has its own seed, so that the formulas do not depend
+    // on the order in which the worker threads generate them
+    let master_seed = cli.seed.unwrap_or_else(rand::random);
+    println!("Seed: {}", master_seed);
+
+    let ps: Vec<Option<f64>> = if cli.p.is_empty() {
+        vec![None]
+    } else {
+        cli.p.iter().copied().map(Some).collect()
+    };
+    let mut all = vec![];
+    for &problem in cli.problem.iter() {
+        for &k in cli.k.iter() {
+            for &p in ps.iter() {
+                for &n in cli.n.iter() {
+                    if cli.verbose {
+                        match p {
+                            Some(p) => println!("{}, p = {}, n = {}", problem.label(&k.to_string()), p, n),
+                            None => println!("{}, n = {}", problem.label(&k.to_string()), n),
+                        }
+                    }
+                    all.push(run_sweep(cli, problem, k, n, p, master_seed));
+                }
+            }
+        }
+    }
+
+    let several = all.len() > 1;
+    for results in all.iter() {
+        if let Some(path) = &cli.output_csv {
+            write_results(&output_path(path, &results.config, several), results, false);
+        }
+        if let Some(path) = &cli.output_json {
+            write_results(&output_path(path, &results.config, several), results, true);
+        }
+    }
+    all
+}
+
+
+fn sweep(cli: SweepArgs) {
+    let all = run_sweeps(&cli);
+
+    let title = chart_title(&all);
+    let values = probability_series(&all, parameter_range(&cli));
+    if let Some(cost) = cli.cost {
+        let medians = series(&all, |point| point.median_cost);
+        let means = series(&all, |point| point.mean_cost);
+        let overlay = cli.overlay.then_some(values.as_slice());
+        let options = plot_options(format!("{} - {}", title, cost.description()), &all);
+        match plot_cost(&options, cost.description(), &medians, &means, overlay) {
+            Ok(path) => println!("Generated file {}", path),
+            Err(err) => panic!("An error occurred while generating the plot: {:?}", err),
+        }
+    }
+    let fits = fit_transitions(&all, cli.fit);
+    match plot_series(&plot_options(title, &all), &values, &fits) {
         Ok(path) => println!("Generated file {}", path),
         Err(err) => panic!("An error occurred while generating the plot: {:?}", err),
     }
 }
+
+/// Samples solved for a value of alpha
+struct Tally {
+    /// Index of alpha on the finest grid of the sweep
+    alpha_idx: usize,
+    alpha: f32,
+    satisfiable: u32,
+    times: Vec<f64>,
+    costs: Vec<f64>,
+}
+
+impl Tally {
+    fn new(alpha_idx: usize, alpha: f32) -> Self {
+        Tally {
+            alpha_idx,
+            alpha,
+            satisfiable: 0,
+            times: vec![],
+            costs: vec![],
+        }
+    }
+
+    fn samples(&self) -> u32 {
+        self.times.len() as u32
+    }
+
+    fn estimate(&self, cli: &SweepArgs) -> Estimate {
+        let (lower, upper) = cli.interval.bounds(self.satisfiable, self.samples(), cli.confidence);
+        Estimate {
+            alpha_idx: self.alpha_idx,
+            samples: self.samples(),
+            satisfiable: self.satisfiable,
+            lower,
+            upper,
+        }
+    }
+
+    fn point(&self, cli: &SweepArgs) -> SweepPoint {
+        let estimate = self.estimate(cli);
+        SweepPoint {
+            alpha: self.alpha,
+            alpha_index: self.alpha_idx,
+            samples: estimate.samples,
+            satisfiable: estimate.satisfiable,
+            lower: estimate.lower,
+            upper: estimate.upper,
+            mean_time: stats::mean(&self.times),
+            median_cost: cli.cost.map(|_| stats::median(&self.costs)),
+            mean_cost: cli.cost.map(|_| stats::mean(&self.costs)),
+        }
+    }
+}
+
+/// Sample and solve `count` more random formulas of a sweep, drawn from
+/// the ensemble of the value of its parameter of `tally`
+fn solve_samples(
+    cli: &SweepArgs,
+    solver: &dyn Solver,
+    config: &SweepConfig,
+    ensemble: &Ensemble,
+    tally: &mut Tally,
+    count: u32,
+) {
+    if cli.verbose {
+        println!("{} = {}", config.parameter(), tally.alpha);
+    }
+    let (alpha_idx, first) = (tally.alpha_idx, tally.times.len());
+    let results = parallel_map(count as usize, cli.jobs, |sample| {
+        let sample = first + sample;
+        let (formula, planted) = ensemble.sample(sample_seed(config.seed, alpha_idx, sample));
+        if let Some(dir) = &cli.dump_dimacs {
+            let command = generate_command(ensemble, config.seed, alpha_idx, sample);
+            let comments = formula_comments(command, planted.as_deref());
+            dump_formula(dir, dump_name(ensemble, sample), &comments, &formula, ensemble.n);
+        }
+        let start = Instant::now();
+        let solution = match &formula {
+            Formula::Cnf(formula) => solver.solve(formula),
+            Formula::Xor(formula) => GaussSolver.solve(formula),
+        };
+        let seconds = start.elapsed().as_secs_f64();
+        let checked = match (&solution.model, &planted) {
+            _ if !cli.check => Ok(()),
+            (Some(model), _) => check_model(&formula, model),
+            (None, Some(_)) => Err(format!("The solver found no model of planted formula {}", formula)),
+            (None, None) => Ok(()),
+        };
+        if let Err(err) = checked {
+            eprintln!(
+                "{}\nsolver: {}, seed: {}, {}: {}, sample: {}\nregenerate it with: {}",
+                err,
+                match formula {
+                    Formula::Cnf(_) => solver.name(),
+                    Formula::Xor(_) => GaussSolver.name(),
+                },
+                config.seed,
+                config.parameter(),
+                ensemble.alpha,
+                sample,
+                generate_command(ensemble, config.seed, alpha_idx, sample)
+            );
+            process::exit(1);
+        }
+        (solution, seconds)
+    });
+    for (solution, seconds) in results {
+        tally.satisfiable += solution.is_sat() as u32;
+        if let Some(cost) = cli.cost {
+            tally.costs.push(cost.measure(&solution, seconds));
+        }
+        tally.times.push(seconds);
+    }
+}
+
+/// Sample and solve random formulas of a problem with n variables and k
+/// variables per constraint (or a fraction p of clauses of length k, if
+/// mixed) for each value of the parameter of the grid, then, in adaptive
+/// mode, add samples and values near the transition until the target
+/// precision is reached
+fn run_sweep(cli: &SweepArgs, problem: Problem, k: u8, n: u32, p: Option<f64>, master_seed: u64) -> SweepResults {
+    // Symmetry breaking adds unit clauses to the 2-colouring formulas
+    let solver = cli.solver.solver(k == 2 && !(problem == Problem::Colouring && cli.symmetry_breaking));
+    let range = parameter_range(cli);
+    let config = SweepConfig {
+        k,
+        n,
+        seed: master_seed,
+        merged_seeds: vec![],
+        problem,
+        generator: cli.generator,
+        p,
+        alpha: cli.vary_p,
+        alpha_start: *range.start(),
+        alpha_end: *range.end(),
+        alpha_steps: cli.alpha_steps,
+        refine: cli.adaptive.then_some(cli.refine),
+        symmetry_breaking: cli.symmetry_breaking,
+        solver: match problem {
+            Problem::XorSat => GaussSolver.name().to_string(),
+            _ => solver.name().to_string(),
+        },
+        cost: cli.cost.as_ref().map(value_name),
+        interval: cli.interval,
+        confidence: cli.confidence,
+        version: env!("CARGO_PKG_VERSION").to_string(),
+    };
+    let refinement = Refinement {
+        precision: cli.precision,
+        max_samples: cli.max_samples.max(cli.samples),
+        band: (0.05, 0.95),
+    };
+    // Values of alpha are indexed on the finest grid the refinement can reach
+    let levels = config.refine.unwrap_or(0);
+    let steps = cli.alpha_steps << levels;
+    let alpha = |alpha_idx: usize| range.start() + (range.end() - range.start()) * (alpha_idx as f32) / steps as f32;
+    // With `--vary-p`, alpha is fixed and p is the swept value, computed in
+    // f64 from its index
+    let ensemble = |alpha_idx: usize| Ensemble {
+        problem,
+        k,
+        n,
+        alpha: cli.vary_p.unwrap_or_else(|| alpha(alpha_idx)),
+        generator: cli.generator,
+        p: cli.vary_p.map_or(p, |_| Some(alpha_idx as f64 / steps as f64)),
+        symmetry_breaking: cli.symmetry_breaking,
+    };
+    let mut tallies: Vec<Tally> = (0..=cli.alpha_steps)
+        .map(|i| i << levels)
+        .map(|alpha_idx| Tally::new(alpha_idx, alpha(alpha_idx)))
+        .collect();
+    for tally in tallies.iter_mut() {
+        solve_samples(cli, solver, &config, &ensemble(tally.alpha_idx), tally, cli.samples);
+    }
+
+    if cli.adaptive {
+        let mut rounds = 0;
+        loop {
+            let estimates: Vec<Estimate> = tallies.iter().map(|tally| tally.estimate(cli)).collect();
+            let round = refinement.next_round(&estimates);
+            if round.is_empty() {
+                break;
+            }
+            rounds += 1;
+            if cli.verbose {
+                println!(
+                    "round {}: {} more samples for {} values of alpha, {} new values of alpha",
+                    rounds,
+                    round.samples.iter().map(|&(_, samples)| samples).sum::<u32>(),
+                    round.samples.len(),
+                    round.insertions.len()
+                );
+            }
+            for (alpha_idx, samples) in round.samples {
+                let tally = tallies.iter_mut().find(|tally| tally.alpha_idx == alpha_idx).unwrap();
+                solve_samples(cli, solver, &config, &ensemble(alpha_idx), tally, samples);
+            }
+            for alpha_idx in round.insertions {
+                let mut tally = Tally::new(alpha_idx, alpha(alpha_idx));
+                solve_samples(cli, solver, &config, &ensemble(alpha_idx), &mut tally, cli.samples);
+                tallies.push(tally);
+            }
+            tallies.sort_by_key(|tally| tally.alpha_idx);
+        }
+        println!(
+            "{}: {} samples for {} values of alpha after {} rounds",
+            sweep_name(&config),
+            tallies.iter().map(Tally::samples).sum::<u32>(),
+            tallies.len(),
+            rounds
+        );
+    }
+
+    let points: Vec<SweepPoint> = tallies.iter().map(|tally| tally.point(cli)).collect();
+    if cli.verbose {
+        println!("alphas: {:?}", points.iter().map(|point| point.alpha).collect::<Vec<_>>());
+        println!("values: {:?}", points.iter().map(SweepPoint::probability).collect::<Vec<_>>());
+        if cli.cost.is_some() {
+            println!("medians: {:?}", points.iter().filter_map(|point| point.median_cost).collect::<Vec<_>>());
+            println!("means: {:?}", points.iter().filter_map(|point| point.mean_cost).collect::<Vec<_>>());
+        }
+    }
+
+    SweepResults {
+        config,
+        points,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn results(k: u8, n: u32) -> SweepResults {
+        SweepResults {
+            config: SweepConfig {
+                k,
+                n,
+                seed: 1,
+                merged_seeds: vec![],
+                problem: Problem::Sat,
+                generator: Generator::Uniform,
+                p: None,
+                alpha: None,
+                alpha_start: 0.,
+                alpha_end: 10.,
+                alpha_steps: 100,
+                refine: None,
+                symmetry_breaking: false,
+                solver: "dpll".to_string(),
+                cost: None,
+                interval: Interval::Wilson,
+                confidence: 0.95,
+                version: "0.1.0".to_string(),
+            },
+            points: vec![SweepPoint {
+                alpha: 4.,
+                alpha_index: 40,
+                samples: 100,
+                satisfiable: 50,
+                lower: 0.4,
+                upper: 0.6,
+                mean_time: 0.001,
+                median_cost: None,
+                mean_cost: None,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_labels() {
+        // Only the parameters which differ between the series are shown
+        let all = [results(3, 50), results(3, 100), results(4, 50), results(4, 100)];
+        let labels: Vec<String> = all.iter().map(|results| series_label(&results.config, &all)).collect();
+        assert_eq!(vec!["k=3, N=50", "k=3, N=100", "k=4, N=50", "k=4, N=100"], labels);
+        assert_eq!("3,4-SAT, N=50,100 (100 s.p.p.)", chart_title(&all));
+        let all = [results(3, 50), results(4, 50)];
+        assert_eq!("k=4", series_label(&all[1].config, &all));
+        let all = [results(3, 50)];
+        assert_eq!("N=50", series_label(&all[0].config, &all));
+
+        let mut planted = results(3, 50);
+        planted.config.generator = Generator::Planted;
+        let all = [results(3, 50), planted];
+        assert_eq!("N=50, planted", series_label(&all[1].config, &all));
+        let mut xor = results(3, 50);
+        xor.config.problem = Problem::XorSat;
+        let all = [results(3, 50), xor];
+        assert_eq!("3-XORSAT", series_label(&all[1].config, &all));
+        assert_eq!("3-SAT/3-XORSAT, N=50 (100 s.p.p.)", chart_title(&all));
+    }
+
+    #[test]
+    fn test_output_path() {
+        let mut config = results(3, 50).config;
+        assert_eq!(PathBuf::from("out/r.csv"), output_path(Path::new("out/r.csv"), &config, false));
+        assert_eq!(PathBuf::from("out/r-3-sat-n-50.csv"), output_path(Path::new("out/r.csv"), &config, true));
+        config.p = Some(0.4);
+        assert_eq!(PathBuf::from("r-3-sat-p-0.4-n-50"), output_path(Path::new("r"), &config, true));
+    }
+
+    #[test]
+    fn test_solution_model() {
+        // x_1 only occurs in a tautology and x_4 in no clause
+        let (formula, variables) = parse_dimacs("p cnf 4 2\n1 -1 0\n2 3 0\n").unwrap();
+        for choice in [SolverChoice::Dpll, SolverChoice::Cdcl, SolverChoice::TwoSat] {
+            let model = choice.solver(true).solve(&formula).model.unwrap();
+            let model = solution_model(Formula::Cnf(formula.clone()), &model, variables).unwrap();
+            assert_eq!(4, model.len());
+            assert!(model.iter().all(Option::is_some));
+        }
+        // Don't-cares are completed as false, which can falsify the formula
+        let model = vec![None, Some(true), None];
+        assert_eq!(
+            Ok(vec![Some(false), Some(true), Some(false), Some(false)]),
+            solution_model(Formula::Cnf(formula.clone()), &model, variables)
+        );
+        assert!(solution_model(Formula::Cnf(formula), &vec![None, None, None], variables).is_err());
+    }
+
+    #[test]
+    fn test_dump_name() {
+        let mut ensemble = Ensemble {
+            problem: Problem::Sat,
+            k: 3,
+            n: 20,
+            alpha: 4.,
+            generator: Generator::Uniform,
+            p: None,
+            symmetry_breaking: false,
+        };
+        assert_eq!("3-sat-uniform-n-20-alpha-4-0.cnf", dump_name(&ensemble, 0));
+        // Values closer than 0.001 on a refined grid
+        ensemble.alpha = 4.0002;
+        ensemble.p = Some(0.40005);
+        assert_eq!("3-sat-uniform-n-20-p-0.40005-alpha-4.0002-1.cnf", dump_name(&ensemble, 1));
+    }
+
+    #[test]
+    fn test_validate_n() {
+        let generate_args = |command: &str| {
+            let Some(Command::Generate(args)) = Cli::try_parse_from(command.split(' ')).unwrap().command else {
+                panic!("expected the generate subcommand");
+            };
+            args
+        };
+        // k is the number of colours in graph colouring, which can exceed n
+        let args = generate_args("satisfaction generate --problem colouring -k 3 -n 2 --alpha 1 --seed 1");
+        assert_eq!(Ok(()), validate_n(args.problem, args.k, args.n));
+        let (Formula::Cnf(formula), _) = args.ensemble().sample(0) else {
+            panic!("expected a CNF formula");
+        };
+        assert_eq!(6, formula.num_variables());
+        assert_eq!(Err("graph colouring requires n >= 1"), validate_n(Problem::Colouring, 3, 0));
+
+        let args = generate_args("satisfaction generate --problem nae -k 3 -n 2 --alpha 1 --seed 1");
+        assert_eq!(Err("n must be at least k"), validate_n(args.problem, args.k, args.n));
+    }
+
+    #[test]
+    fn test_generate_command() {
+        let ensemble = Ensemble {
+            problem: Problem::NaeSat,
+            k: 4,
+            n: 30,
+            alpha: 2.5,
+            generator: Generator::Regular,
+            p: Some(0.6666666666666666),
+            symmetry_breaking: false,
+        };
+        let command = generate_command(&ensemble, 7, 12, 3);
+        assert_eq!(
+            "satisfaction generate --problem nae -k 4 -n 30 --alpha 2.5 --generator regular --p 0.6666666666666666 \
+             --seed 7 --alpha-index 12 --sample 3",
+            command
+        );
+        // The command parses back to the same formula
+        let Some(Command::Generate(args)) = Cli::try_parse_from(command.split(' ')).unwrap().command else {
+            panic!("expected the generate subcommand");
+        };
+        assert_eq!(ensemble, args.ensemble());
+        assert_eq!((7, 12, 3), (args.seed, args.alpha_index, args.sample));
+
+        let colouring = Ensemble {
+            problem: Problem::Colouring,
+            k: 3,
+            n: 50,
+            alpha: 4.5,
+            generator: Generator::Uniform,
+            p: None,
+            symmetry_breaking: true,
+        };
+        assert_eq!(
+            "satisfaction generate --problem colouring -k 3 -n 50 --alpha 4.5 --symmetry-breaking --seed 1 --alpha-index 0 --sample 0",
+            generate_command(&colouring, 1, 0, 0)
+        );
+
+        // The master seed of a sweep determines its formulas
+        let cli = Cli::try_parse_from(["satisfaction", "--seed", "7"]).unwrap();
+        assert_eq!(Some(7), cli.sweep.seed);
+        let seed = sample_seed(args.seed, args.alpha_index, args.sample);
+        assert_eq!(ensemble.sample(seed), ensemble.sample(seed));
+        assert_ne!(ensemble.sample(seed), ensemble.sample(sample_seed(7, 12, 4)));
+    }
+}
@@ -0,0 +1,27 @@
+use clap::ValueEnum;
+
+/// Name of a value of an enumeration on the command line, which is also
+/// the one written in the saved results
+pub fn value_name<T: ValueEnum>(value: &T) -> String {
+    value.to_possible_value().unwrap().get_name().to_string()
+}
+
+/// Value of an enumeration from its name on the command line
+pub fn parse_value<T: ValueEnum>(name: &str) -> Option<T> {
+    T::from_str(name, false).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problem::Problem;
+    use crate::rand_cnf::Generator;
+
+    #[test]
+    fn test_names() {
+        assert_eq!("1-in-k", value_name(&Problem::OneInK));
+        assert_eq!("quiet-planted", value_name(&Generator::QuietPlanted));
+        assert_eq!(Some(Problem::XorSat), parse_value("xorsat"));
+        assert_eq!(None, parse_value::<Problem>("XORSAT"));
+    }
+}
@@ -0,0 +1,48 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+/// Compute `f(0), ..., f(tasks - 1)` on `jobs` worker threads
+///
+/// Tasks are handed out one at a time to the first idle worker, and the
+/// results are returned in the order of the tasks, whatever the number
+/// of workers. A panic in a worker is propagated to the caller.
+pub fn parallel_map<T: Send>(tasks: usize, jobs: usize, f: impl Fn(usize) -> T + Sync) -> Vec<T> {
+    if jobs <= 1 {
+        return (0..tasks).map(f).collect();
+    }
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new((0..tasks).map(|_| None).collect::<Vec<Option<T>>>());
+    thread::scope(|scope| {
+        for _ in 0..jobs.min(tasks) {
+            scope.spawn(|| loop {
+                let task = next.fetch_add(1, Ordering::Relaxed);
+                if task >= tasks {
+                    break;
+                }
+                let result = f(task);
+                results.lock().unwrap()[task] = Some(result);
+            });
+        }
+    });
+    results
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .map(|result| result.expect("every task has been run"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parallel_map() {
+        let expected: Vec<usize> = (0..100).map(|task| task * task).collect();
+        for jobs in [0, 1, 4, 200] {
+            assert_eq!(expected, parallel_map(100, jobs, |task| task * task));
+        }
+        assert!(parallel_map(0, 4, |task| task).is_empty());
+    }
+}
@@ -1,30 +1,277 @@
 use plotters::prelude::*;
 use slugify::slugify;
+use std::ops::Range;
+use std::path::PathBuf;
 
+use crate::fit::Fit;
 
-pub fn plot_series(title: String, alphas: Vec<f32>, results: Vec<f32>) -> Result<String, Box<dyn std::error::Error>> {
-    let slug = slugify!(&title);
-    let path = format!("{}.png", slug);
-    let root = BitMapBackend::new(&path, (640, 480)).into_drawing_area();
+
+/// Title, size and ranges of a chart
+pub struct PlotOptions {
+    pub title: String,
+    /// Width and height of the image, in pixels
+    pub size: (u32, u32),
+    /// Range of values of alpha (that of the series by default)
+    pub alpha_range: Option<Range<f32>>,
+    /// Description of the horizontal axis
+    pub x_desc: String,
+    /// Name of the fitted thresholds in the legend
+    pub threshold: String,
+    /// Path of the PNG file (derived from the title by default)
+    pub path: Option<PathBuf>,
+}
+
+impl PlotOptions {
+    pub fn new(title: String) -> Self {
+        PlotOptions {
+            title,
+            size: (640, 480),
+            alpha_range: None,
+            x_desc: "α (ratio of clauses to variables)".to_string(),
+            threshold: "α_c".to_string(),
+            path: None,
+        }
+    }
+
+    fn path(&self) -> String {
+        match &self.path {
+            Some(path) => path.display().to_string(),
+            None => format!("{}.png", slugify!(&self.title)),
+        }
+    }
+
+    fn alpha_range(&self, alphas: &[f32]) -> Range<f32> {
+        self.alpha_range.clone().unwrap_or(alphas[0]..alphas[alphas.len() - 1])
+    }
+}
+
+
+/// A curve of a chart: values for a list of values of alpha
+pub struct Series<T> {
+    /// Label of the curve in the legend
+    pub label: String,
+    pub alphas: Vec<f32>,
+    pub values: Vec<T>,
+    /// Lower and upper bounds of the confidence interval of each value, if known
+    pub bounds: Option<Vec<(T, T)>>,
+}
+
+/// Colours of the successive series of a chart
+const COLORS: [RGBColor; 6] = [
+    RED,
+    BLUE,
+    RGBColor(0, 150, 0),
+    MAGENTA,
+    RGBColor(230, 140, 0),
+    RGBColor(0, 170, 170),
+];
+
+fn color(idx: usize) -> RGBColor {
+    COLORS[idx % COLORS.len()]
+}
+
+/// Marker of the points of the idx-th series (a circle, a triangle, a square
+/// or a diamond), as the vertices of a polygon around (0, 0) in pixels
+fn marker(idx: usize, center: (i32, i32)) -> Vec<(i32, i32)> {
+    let vertices = match idx % 4 {
+        0 => vec![(-1, -3), (1, -3), (3, -1), (3, 1), (1, 3), (-1, 3), (-3, 1), (-3, -1)],
+        1 => vec![(0, -4), (4, 3), (-4, 3)],
+        2 => vec![(-3, -3), (3, -3), (3, 3), (-3, 3)],
+        _ => vec![(0, -4), (4, 0), (0, 4), (-4, 0)],
+    };
+    vertices.into_iter().map(|(x, y)| (center.0 + x, center.1 + y)).collect()
+}
+
+/// Plot the share of satisfiable formulas against alpha, with a legend
+/// identifying the series if there are several of them and a shaded band
+/// showing the confidence intervals of the series which have some
+///
+/// `fits` holds the models fitted to the series, if any: they are drawn as
+/// dashed curves with a dotted vertical line at their threshold, which is
+/// given in the legend.
+pub fn plot_series(
+    options: &PlotOptions,
+    series: &[Series<f32>],
+    fits: &[Option<Fit>],
+) -> Result<String, Box<dyn std::error::Error>> {
+    let path = options.path();
+    let root = BitMapBackend::new(&path, options.size).into_drawing_area();
     root.fill(&WHITE)?;
     let mut chart = ChartBuilder::on(&root)
-        .caption(title, ("sans-serif", 30).into_font())
+        .caption(&options.title, ("sans-serif", 30).into_font())
         .margin(5)
         .x_label_area_size(30)
         .y_label_area_size(40)
-        .build_cartesian_2d(alphas[0]..alphas[alphas.len() - 1], 0f32..1f32)?;
+        .build_cartesian_2d(options.alpha_range(&series[0].alphas), 0f32..1f32)?;
 
     chart
         .configure_mesh()
-        .x_desc("α (ratio of clauses to variables)")
+        .x_desc(&options.x_desc)
         .y_desc("Share of satisfiable formulas")
         .draw()?;
 
+    for (idx, series) in series.iter().enumerate() {
+        if let Some(bounds) = &series.bounds {
+            let band = series
+                .alphas
+                .iter()
+                .zip(bounds)
+                .map(|(&alpha, &(_, upper))| (alpha, upper))
+                .chain(series.alphas.iter().zip(bounds).rev().map(|(&alpha, &(lower, _))| (alpha, lower)));
+            chart.draw_series(std::iter::once(Polygon::new(band.collect::<Vec<_>>(), color(idx).mix(0.2).filled())))?;
+        }
+    }
+    let x_range = options.alpha_range(&series[0].alphas);
+    for (idx, fit) in fits.iter().enumerate() {
+        let Some(fit) = fit else {
+            continue;
+        };
+        let color = color(idx);
+        let curve = (0..=200).map(|i| {
+            let alpha = x_range.start + (x_range.end - x_range.start) * i as f32 / 200.;
+            (alpha, fit.probability(alpha as f64) as f32)
+        });
+        chart.draw_series(DashedLineSeries::new(curve, 6, 4, color.stroke_width(1)))?;
+        let alpha_c = fit.alpha_c as f32;
+        if x_range.contains(&alpha_c) {
+            chart.draw_series(DashedLineSeries::new([(alpha_c, 0.), (alpha_c, 1.)], 3, 3, color.stroke_width(2)))?;
+        }
+    }
+
+    for (idx, series) in series.iter().enumerate() {
+        let color = color(idx);
+        let points = series.alphas.iter().copied().zip(series.values.iter().copied());
+        let label = match fits.get(idx) {
+            Some(Some(fit)) => format!(
+                "{} ({} = {:.3} ± {:.3})",
+                series.label, options.threshold, fit.alpha_c, fit.alpha_c_error
+            ),
+            _ => series.label.clone(),
+        };
+        chart
+            .draw_series(LineSeries::new(points.clone(), color.stroke_width(2)))?
+            .label(label)
+            .legend(move |(x, y)| {
+                EmptyElement::at((x, y))
+                    + PathElement::new(vec![(0, 0), (20, 0)], color.stroke_width(2))
+                    + Polygon::new(marker(idx, (10, 0)), color.filled())
+            });
+        chart.draw_series(points.map(|point| EmptyElement::at(point) + Polygon::new(marker(idx, (0, 0)), color.filled())))?;
+    }
+
+    if series.len() > 1 || fits.iter().any(Option::is_some) {
+        chart
+            .configure_series_labels()
+            .background_style(WHITE.mix(0.8))
+            .border_style(BLACK)
+            .draw()?;
+    }
+
+    root.present()?;
+
+    Ok(path.clone())
+}
+
+
+/// Plot the median and mean cost of the solver for each value of alpha,
+/// on a logarithmic scale, optionally overlaying the share of satisfiable
+/// formulas on a secondary axis
+///
+/// `medians`, `means` and `overlay` hold one series per curve of the chart.
+/// Costs below the smallest positive cost (e.g. no decision at all) are
+/// drawn at the bottom of the chart, which a logarithmic scale cannot reach.
+pub fn plot_cost(
+    options: &PlotOptions,
+    cost_desc: &str,
+    medians: &[Series<f64>],
+    means: &[Series<f64>],
+    overlay: Option<&[Series<f32>]>,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let path = options.path();
+    let root = BitMapBackend::new(&path, options.size).into_drawing_area();
+    root.fill(&WHITE)?;
+
+    let positive = medians
+        .iter()
+        .chain(means.iter())
+        .flat_map(|series| series.values.iter().copied())
+        .filter(|&cost| cost > 0.);
+    let low = positive.clone().fold(f64::INFINITY, f64::min);
+    let high = positive.fold(0., f64::max);
+    let (low, high) = if low.is_finite() { (low, high.max(low * 10.)) } else { (0.1, 1.) };
+    let x_range = options.alpha_range(&medians[0].alphas);
+    let mut chart = ChartBuilder::on(&root)
+        .caption(&options.title, ("sans-serif", 30).into_font())
+        .margin(5)
+        .x_label_area_size(30)
+        .y_label_area_size(60)
+        .right_y_label_area_size(if overlay.is_some() { 40 } else { 0 })
+        .build_cartesian_2d(x_range.clone(), (low..high).log_scale())?
+        .set_secondary_coord(x_range, 0f32..1f32);
+
     chart
-        .draw_series(LineSeries::new(
-            alphas.into_iter().zip(results.into_iter()),
-            Into::<ShapeStyle>::into(&RED).stroke_width(2),
+        .configure_mesh()
+        .x_desc(&options.x_desc)
+        .y_desc(cost_desc)
+        .y_label_formatter(&|&cost| {
+            if cost >= 1. {
+                format!("{:.0}", cost)
+            } else {
+                format!("{:.0e}", cost)
+            }
+        })
+        .draw()?;
+
+    // With several series, the legend identifies the series by their
+    // medians, means being thinner and shares of satisfiable formulas dashed
+    let several = medians.len() > 1;
+    let clamp = |cost: f64| cost.max(low);
+    for (idx, (median, mean)) in medians.iter().zip(means).enumerate() {
+        let color = if several { color(idx) } else { BLUE };
+        chart
+            .draw_series(LineSeries::new(
+                median.alphas.iter().copied().zip(median.values.iter().copied().map(clamp)),
+                color.stroke_width(2),
+            ))?
+            .label(if several { median.label.as_str() } else { "Median" })
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color.stroke_width(2)));
+        let means = chart.draw_series(LineSeries::new(
+            mean.alphas.iter().copied().zip(mean.values.iter().copied().map(clamp)),
+            color.mix(0.5).stroke_width(1),
         ))?;
+        if !several {
+            means
+                .label("Mean")
+                .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color.mix(0.5)));
+        }
+    }
+
+    if let Some(overlay) = overlay {
+        chart
+            .configure_secondary_axes()
+            .y_desc("Share of satisfiable formulas")
+            .draw()?;
+        for (idx, series) in overlay.iter().enumerate() {
+            let color = if several { color(idx) } else { RED };
+            let shares = chart.draw_secondary_series(DashedLineSeries::new(
+                series.alphas.iter().copied().zip(series.values.iter().copied()),
+                6,
+                4,
+                color.stroke_width(2),
+            ))?;
+            if !several {
+                shares
+                    .label("Satisfiable")
+                    .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color.stroke_width(2)));
+            }
+        }
+    }
+
+    chart
+        .configure_series_labels()
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()?;
 
     root.present()?;
 
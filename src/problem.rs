@@ -0,0 +1,115 @@
+use clap::ValueEnum;
+use std::fmt;
+
+use crate::cnf::{CNFFormula, Evaluation, Semantics};
+use crate::colouring::{encode_colouring, generate_graph};
+use crate::rand_cnf::{Generator, Lengths};
+use crate::xorsat::{generate_xor, XorFormula};
+
+/// Kind of constraints of the random formulas of a sweep
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum Problem {
+    /// k-SAT, whose clauses are satisfied by at least one of their k literals
+    Sat,
+    /// k-XORSAT, whose constraints fix the parity of the sum of their k
+    /// variables, solved by Gaussian elimination
+    #[value(name = "xorsat")]
+    XorSat,
+    /// k-NAE-SAT, whose clauses have at least one true and one false literal
+    #[value(name = "nae")]
+    NaeSat,
+    /// 1-in-k SAT, whose clauses have exactly one true literal
+    #[value(name = "1-in-k")]
+    OneInK,
+    /// k-colouring of Erdős–Rényi graphs of n vertices and average degree c
+    /// (given by alpha), encoded into CNF
+    Colouring,
+}
+
+impl Problem {
+    /// Name of the problem for the given value(s) of k, e.g. "3-SAT",
+    /// "3-XORSAT", "3-NAE-SAT", "1-in-3-SAT" or "3-colouring"
+    pub fn label(self, k: &str) -> String {
+        match self {
+            Problem::Sat => format!("{}-SAT", k),
+            Problem::XorSat => format!("{}-XORSAT", k),
+            Problem::NaeSat => format!("{}-NAE-SAT", k),
+            Problem::OneInK => format!("1-in-{}-SAT", k),
+            Problem::Colouring => format!("{}-colouring", k),
+        }
+    }
+
+    /// Semantics of the clauses of the problem, whose formulas are encoded
+    /// into CNF, or `None` for XOR-SAT and graph colouring
+    pub fn semantics(self) -> Option<Semantics> {
+        match self {
+            Problem::Sat => Some(Semantics::AtLeastOne),
+            Problem::XorSat => None,
+            Problem::NaeSat => Some(Semantics::NotAllEqual),
+            Problem::OneInK => Some(Semantics::ExactlyOne),
+            Problem::Colouring => None,
+        }
+    }
+}
+
+/// A random formula of one of the problems
+#[derive(Clone, Debug, PartialEq)]
+pub enum Formula {
+    Cnf(CNFFormula),
+    Xor(XorFormula),
+}
+
+impl Formula {
+    pub fn evaluate(&self, assignment: &[Option<bool>]) -> Evaluation {
+        match self {
+            Formula::Cnf(formula) => formula.evaluate(assignment),
+            Formula::Xor(formula) => formula.evaluate(assignment),
+        }
+    }
+}
+
+impl fmt::Display for Formula {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Formula::Cnf(formula) => formula.fmt(f),
+            Formula::Xor(formula) => formula.fmt(f),
+        }
+    }
+}
+
+/// Random formulas of a problem for one value of alpha (or p), from which
+/// the samples of a sweep are drawn
+#[derive(Clone, Debug, PartialEq)]
+pub struct Ensemble {
+    pub problem: Problem,
+    pub k: u8,
+    pub n: u32,
+    pub alpha: f32,
+    pub generator: Generator,
+    /// Fraction of clauses of length k in mixed formulas, the others having length 2
+    pub p: Option<f64>,
+    pub symmetry_breaking: bool,
+}
+
+impl Ensemble {
+    /// Draw the formula of a seed, with its hidden assignment if it is planted
+    pub fn sample(&self, seed: u64) -> (Formula, Option<Vec<bool>>) {
+        let lengths = match self.p {
+            Some(p) => Lengths::mixed(self.k, p),
+            None => Lengths::fixed(self.k),
+        };
+        // The clauses of NAE-SAT and 1-in-k SAT are drawn as those of k-SAT,
+        // then encoded into CNF
+        match (self.problem, self.problem.semantics()) {
+            (_, Some(semantics)) => {
+                let (formula, planted) = self.generator.generate(&lengths, self.n, self.alpha, Some(seed));
+                (Formula::Cnf(formula.encode(semantics)), planted)
+            }
+            (Problem::Colouring, None) => {
+                let graph = generate_graph(self.n, self.alpha, Some(seed));
+                (Formula::Cnf(encode_colouring(&graph, self.k, self.symmetry_breaking)), None)
+            }
+            (_, None) => (Formula::Xor(generate_xor(self.k, self.n, self.alpha, Some(seed))), None),
+        }
+    }
+}
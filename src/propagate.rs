@@ -0,0 +1,443 @@
+use std::cmp::Reverse;
+
+use crate::cnf::{Assignment, CNFFormula, CNFLiteral};
+
+/// Literal encoded as `2 * variable + negate`
+pub type Literal = u32;
+
+/// Position of a clause in the arena
+pub type ClauseRef = u32;
+
+#[inline(always)]
+pub fn encode(literal: &CNFLiteral) -> Literal {
+    2 * literal.variable + literal.negate as u32
+}
+
+#[inline(always)]
+pub fn variable(literal: Literal) -> usize {
+    (literal >> 1) as usize
+}
+
+/// Header flags of the clauses in the arena, the length
+/// of the clause being stored in the remaining bits
+const LEARNED: u32 = 1;
+const DELETED: u32 = 2;
+const FLAGS: u32 = 2;
+
+#[derive(Clone, Copy)]
+struct Watcher {
+    clause: ClauseRef,
+    /// Another literal of the clause: when it is true,
+    /// the clause can be skipped without being read
+    blocker: Literal,
+}
+
+/// Unit propagation engine based on two watched literals
+///
+/// Clauses are stored contiguously in an arena, each one as a header
+/// (length and flags) followed by its literals; the first two literals
+/// of a clause are the watched ones, and the literal implied by a clause
+/// is always its first one. Assignments are recorded on a trail split
+/// into decision levels, so that backtracking only undoes the assignments
+/// above the target level, without touching the clauses.
+///
+/// See <https://www.cs.cmu.edu/~mheule/15816-f23/slides/watched.pdf>
+pub struct Propagator {
+    arena: Vec<u32>,
+    /// Number of arena cells used by deleted clauses
+    wasted: usize,
+    learned: Vec<ClauseRef>,
+    watches: Vec<Vec<Watcher>>,
+    values: Assignment,
+    levels: Vec<usize>,
+    reasons: Vec<Option<ClauseRef>>,
+    trail: Vec<Literal>,
+    /// Position in the trail of each decision
+    trail_lim: Vec<usize>,
+    /// Position in the trail of the next literal to propagate
+    head: usize,
+    /// Number of literals implied by unit propagation
+    propagations: u64,
+}
+
+impl Propagator {
+    /// Load a formula, returning `None` when it is trivially
+    /// unsatisfiable (empty or conflicting unit clauses)
+    ///
+    /// Duplicate literals are merged and tautologies dropped, unit
+    /// clauses are assigned at level 0 without being stored.
+    pub fn new(formula: &CNFFormula) -> Option<Self> {
+        let n = formula.num_variables();
+        let mut propagator = Propagator {
+            arena: vec![],
+            wasted: 0,
+            learned: vec![],
+            watches: vec![vec![]; 2 * n],
+            values: vec![None; n],
+            levels: vec![0; n],
+            reasons: vec![None; n],
+            trail: vec![],
+            trail_lim: vec![],
+            head: 0,
+            propagations: 0,
+        };
+        for clause in formula.clauses.iter() {
+            let mut literals: Vec<Literal> = clause.literals.iter().map(encode).collect();
+            literals.sort_unstable();
+            literals.dedup();
+            if literals.windows(2).any(|pair| pair[0] ^ 1 == pair[1]) {
+                continue;
+            }
+            match literals[..] {
+                [] => return None,
+                [literal] => match propagator.value(literal) {
+                    Some(false) => return None,
+                    Some(true) => (),
+                    None => propagator.assign(literal, None),
+                },
+                _ => {
+                    propagator.insert(&literals, 0);
+                }
+            }
+        }
+        Some(propagator)
+    }
+
+    pub fn num_variables(&self) -> usize {
+        self.values.len()
+    }
+
+    #[inline(always)]
+    pub fn value(&self, literal: Literal) -> Option<bool> {
+        self.values[variable(literal)].map(|value| value != (literal & 1 == 1))
+    }
+
+    pub fn is_assigned(&self, variable: usize) -> bool {
+        self.values[variable].is_some()
+    }
+
+    /// Current (partial) assignment
+    pub fn assignment(&self) -> &Assignment {
+        &self.values
+    }
+
+    pub fn level(&self) -> usize {
+        self.trail_lim.len()
+    }
+
+    /// Decision level at which a variable was assigned
+    pub fn level_of(&self, variable: usize) -> usize {
+        self.levels[variable]
+    }
+
+    /// Clause which implied the value of a variable, if any
+    pub fn reason(&self, variable: usize) -> Option<ClauseRef> {
+        self.reasons[variable]
+    }
+
+    /// Number of literals implied by unit propagation so far
+    pub fn propagations(&self) -> u64 {
+        self.propagations
+    }
+
+    pub fn trail(&self) -> &[Literal] {
+        &self.trail
+    }
+
+    /// Assignments made above a decision level
+    pub fn trail_above(&self, level: usize) -> &[Literal] {
+        match self.trail_lim.get(level) {
+            Some(&start) => &self.trail[start..],
+            None => &[],
+        }
+    }
+
+    pub fn clause(&self, clause: ClauseRef) -> &[Literal] {
+        let start = clause as usize + 1;
+        &self.arena[start..start + (self.arena[clause as usize] >> FLAGS) as usize]
+    }
+
+    /// Iterate over the clauses of the formula (without learned clauses)
+    pub fn original_clauses(&self) -> impl Iterator<Item = &[Literal]> {
+        let mut position = 0;
+        std::iter::from_fn(move || {
+            while position < self.arena.len() {
+                let header = self.arena[position];
+                let clause = position as ClauseRef;
+                position += 1 + (header >> FLAGS) as usize;
+                if header & (LEARNED | DELETED) == 0 {
+                    return Some(self.clause(clause));
+                }
+            }
+            None
+        })
+    }
+
+    /// Open a new decision level
+    pub fn new_level(&mut self) {
+        self.trail_lim.push(self.trail.len());
+    }
+
+    /// Assign a literal at the current level, with the clause which implied it
+    /// (propagation of its consequences happens in `propagate`)
+    pub fn assign(&mut self, literal: Literal, reason: Option<ClauseRef>) {
+        let variable = variable(literal);
+        self.values[variable] = Some(literal & 1 == 0);
+        self.levels[variable] = self.trail_lim.len();
+        self.reasons[variable] = reason;
+        self.trail.push(literal);
+    }
+
+    /// Propagate the assignments of the trail, returning a conflicting clause if any
+    pub fn propagate(&mut self) -> Option<ClauseRef> {
+        while self.head < self.trail.len() {
+            let falsified = self.trail[self.head] ^ 1;
+            self.head += 1;
+            let mut watchers = std::mem::take(&mut self.watches[falsified as usize]);
+            let mut conflict = None;
+            let mut i = 0;
+            while i < watchers.len() {
+                let Watcher { clause, blocker } = watchers[i];
+                if self.value(blocker) == Some(true) {
+                    i += 1;
+                    continue;
+                }
+                let header = self.arena[clause as usize];
+                if header & DELETED != 0 {
+                    watchers.swap_remove(i);
+                    continue;
+                }
+                let start = clause as usize + 1;
+                let end = start + (header >> FLAGS) as usize;
+                // Make sure the falsified literal is the second watch
+                if self.arena[start] == falsified {
+                    self.arena.swap(start, start + 1);
+                }
+                let other = self.arena[start];
+                if other != blocker && self.value(other) == Some(true) {
+                    watchers[i].blocker = other;
+                    i += 1;
+                    continue;
+                }
+                // Look for a new literal to watch
+                if let Some(k) = (start + 2..end).find(|&k| self.value(self.arena[k]) != Some(false)) {
+                    self.arena.swap(start + 1, k);
+                    self.watches[self.arena[start + 1] as usize].push(Watcher { clause, blocker: other });
+                    watchers.swap_remove(i);
+                    continue;
+                }
+                if self.value(other).is_none() {
+                    self.propagations += 1;
+                    self.assign(other, Some(clause));
+                    i += 1;
+                } else {
+                    conflict = Some(clause);
+                    break;
+                }
+            }
+            self.watches[falsified as usize] = watchers;
+            if conflict.is_some() {
+                return conflict;
+            }
+        }
+        None
+    }
+
+    /// Undo the assignments made above a decision level
+    pub fn backtrack(&mut self, level: usize) {
+        if self.trail_lim.len() <= level {
+            return;
+        }
+        for &literal in self.trail[self.trail_lim[level]..].iter() {
+            self.values[variable(literal)] = None;
+            self.reasons[variable(literal)] = None;
+        }
+        self.trail.truncate(self.trail_lim[level]);
+        self.trail_lim.truncate(level);
+        self.head = self.trail.len();
+    }
+
+    /// Store a learned clause of at least two literals, whose first two
+    /// literals must be suitable watches (the first one is the asserting
+    /// literal, and the second one was assigned at the highest level)
+    pub fn learn(&mut self, literals: &[Literal]) -> ClauseRef {
+        let clause = self.insert(literals, LEARNED);
+        self.learned.push(clause);
+        clause
+    }
+
+    pub fn num_learned(&self) -> usize {
+        self.learned.len()
+    }
+
+    /// Delete the longest half of the learned clauses, except binary
+    /// clauses and the reasons of current assignments, and reclaim
+    /// the space of the arena when half of it is wasted
+    pub fn reduce_learned(&mut self) {
+        let mut candidates: Vec<ClauseRef> = self
+            .learned
+            .iter()
+            .copied()
+            .filter(|&clause| {
+                let literals = self.clause(clause);
+                literals.len() > 2 && self.reasons[variable(literals[0])] != Some(clause)
+            })
+            .collect();
+        candidates.sort_by_key(|&clause| Reverse(self.clause(clause).len()));
+        for &clause in candidates[..candidates.len() / 2].iter() {
+            self.arena[clause as usize] |= DELETED;
+            self.wasted += 1 + self.clause(clause).len();
+        }
+        self.learned
+            .retain(|&clause| self.arena[clause as usize] & DELETED == 0);
+        if 2 * self.wasted > self.arena.len() {
+            self.collect_garbage();
+        }
+    }
+
+    fn insert(&mut self, literals: &[Literal], flags: u32) -> ClauseRef {
+        let clause = self.arena.len() as ClauseRef;
+        self.arena.push((literals.len() as u32) << FLAGS | flags);
+        self.arena.extend_from_slice(literals);
+        self.watches[literals[0] as usize].push(Watcher {
+            clause,
+            blocker: literals[1],
+        });
+        self.watches[literals[1] as usize].push(Watcher {
+            clause,
+            blocker: literals[0],
+        });
+        clause
+    }
+
+    /// Compact the arena, moving clauses and updating references to them
+    fn collect_garbage(&mut self) {
+        let mut arena = Vec::with_capacity(self.arena.len() - self.wasted);
+        let mut position = 0;
+        while position < self.arena.len() {
+            let header = self.arena[position];
+            let len = (header >> FLAGS) as usize;
+            if header & DELETED == 0 {
+                let moved = arena.len() as u32;
+                arena.extend_from_slice(&self.arena[position..position + 1 + len]);
+                // The first literal of the old clause is overwritten with its new position
+                self.arena[position + 1] = moved;
+            }
+            position += 1 + len;
+        }
+        for reason in self.reasons.iter_mut().flatten() {
+            *reason = self.arena[*reason as usize + 1];
+        }
+        for clause in self.learned.iter_mut() {
+            *clause = self.arena[*clause as usize + 1];
+        }
+        self.arena = arena;
+        self.wasted = 0;
+
+        for watchers in self.watches.iter_mut() {
+            watchers.clear();
+        }
+        let mut position = 0;
+        while position < self.arena.len() {
+            let clause = position as ClauseRef;
+            let (first, second) = (self.arena[position + 1], self.arena[position + 2]);
+            self.watches[first as usize].push(Watcher {
+                clause,
+                blocker: second,
+            });
+            self.watches[second as usize].push(Watcher {
+                clause,
+                blocker: first,
+            });
+            position += 1 + (self.arena[position] >> FLAGS) as usize;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cnf::CNFClause;
+
+    fn clause(literals: &[i32]) -> CNFClause {
+        CNFClause {
+            literals: literals
+                .iter()
+                .map(|&literal| CNFLiteral {
+                    negate: literal < 0,
+                    variable: literal.unsigned_abs() - 1,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_propagate_and_backtrack() {
+        // (¬x_{0}∨x_{1})∧(¬x_{1}∨x_{2})∧(¬x_{0}∨¬x_{2}∨x_{3})
+        let formula = CNFFormula {
+            clauses: vec![clause(&[-1, 2]), clause(&[-2, 3]), clause(&[-1, -3, 4])],
+        };
+        let mut propagator = Propagator::new(&formula).unwrap();
+        assert_eq!(3, propagator.original_clauses().count());
+        propagator.new_level();
+        propagator.assign(0, None);
+        assert_eq!(None, propagator.propagate());
+        assert_eq!(&vec![Some(true); 4], propagator.assignment());
+        assert_eq!(&[0, 2, 4, 6], propagator.trail_above(0));
+        assert_eq!(Some(1), propagator.reason(1).map(|clause| propagator.clause(clause)[0] / 2));
+
+        propagator.backtrack(0);
+        assert_eq!(&vec![None; 4], propagator.assignment());
+        propagator.new_level();
+        propagator.assign(7, None);
+        assert_eq!(None, propagator.propagate());
+        assert_eq!(&vec![None, None, None, Some(false)], propagator.assignment());
+    }
+
+    #[test]
+    fn test_conflict() {
+        // x_{0} is a unit clause, implying x_{1} and x_{2} which conflict
+        let formula = CNFFormula {
+            clauses: vec![clause(&[1]), clause(&[-1, 2]), clause(&[-1, 3]), clause(&[-2, -3])],
+        };
+        let mut propagator = Propagator::new(&formula).unwrap();
+        let conflict = propagator.propagate().unwrap();
+        assert!(propagator
+            .clause(conflict)
+            .iter()
+            .all(|&literal| propagator.value(literal) == Some(false)));
+
+        let formula = CNFFormula {
+            clauses: vec![clause(&[1]), clause(&[-1])],
+        };
+        assert!(Propagator::new(&formula).is_none());
+    }
+
+    #[test]
+    fn test_reduce_learned() {
+        let formula = CNFFormula {
+            clauses: vec![clause(&[1, 2, 3, 4])],
+        };
+        let mut propagator = Propagator::new(&formula).unwrap();
+        propagator.learn(&[0, 2, 4]);
+        propagator.learn(&[1, 3, 5, 6]);
+        propagator.learn(&[0, 3]);
+        propagator.reduce_learned();
+        propagator.collect_garbage();
+        let mut learned: Vec<&[Literal]> = propagator
+            .learned
+            .iter()
+            .map(|&clause| propagator.clause(clause))
+            .collect();
+        learned.sort();
+        assert_eq!(vec![&[0, 2, 4][..], &[0, 3][..]], learned);
+        assert_eq!(vec![&[0, 2, 4, 6][..]], propagator.original_clauses().collect::<Vec<_>>());
+
+        // Clauses are still watched after the arena was compacted
+        propagator.new_level();
+        propagator.assign(1, None);
+        propagator.assign(3, None);
+        assert_eq!(None, propagator.propagate());
+        assert_eq!(Some(true), propagator.value(4));
+    }
+}
@@ -1,40 +1,305 @@
-use rand::{rngs::StdRng, Rng, SeedableRng, RngCore};
+use clap::ValueEnum;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng, RngCore};
 
 use crate::cnf::{CNFClause, CNFFormula, CNFLiteral};
 
 
-pub fn generate_cnf(k: u8, n: u32, alpha: f32, seed: Option<u64>) -> CNFFormula {
-    assert!(n >= k.into());
-    let mut rng: Box<dyn RngCore> = match seed {
+/// Derive the seed of a sample of a sweep from the master seed of the sweep
+/// and the indices of its value of alpha and of the sample, mixing them with
+/// the SplitMix64 finalizer so that neighbouring samples get unrelated seeds
+pub fn sample_seed(master: u64, alpha_idx: usize, sample_idx: usize) -> u64 {
+    let mut z = master;
+    for idx in [alpha_idx, sample_idx] {
+        z = z.wrapping_add(0x9e37_79b9_7f4a_7c15).wrapping_add(idx as u64);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+    }
+    z
+}
+
+/// Distribution of the random formulas of a sweep
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum Generator {
+    /// Uniform random k-SAT, whose clauses are drawn independently
+    Uniform,
+    /// Uniform clauses satisfied by a hidden random assignment
+    Planted,
+    /// Planted clauses weighted by their number of true literals, so that the
+    /// signs of the literals do not give the hidden assignment away
+    QuietPlanted,
+    /// Regular random k-SAT, where all variables occur the same number of times
+    Regular,
+    /// Regular random k-SAT, where all variables also occur the same number
+    /// of times positively and negatively
+    RegularBalanced,
+}
+
+impl Generator {
+    /// Generate a formula, with the hidden assignment satisfying it if any
+    pub fn generate(self, lengths: &Lengths, n: u32, alpha: f32, seed: Option<u64>) -> (CNFFormula, Option<Vec<bool>>) {
+        match self {
+            Generator::Uniform => match *lengths.0.as_slice() {
+                [(k, _)] => (generate_cnf(k, n, alpha, seed), None),
+                _ => (generate_mixed_cnf(lengths, n, alpha, seed), None),
+            },
+            Generator::Planted | Generator::QuietPlanted => {
+                let (formula, planted) =
+                    generate_planted_cnf(lengths, n, alpha, self == Generator::QuietPlanted, seed);
+                (formula, Some(planted))
+            }
+            Generator::Regular | Generator::RegularBalanced => {
+                (generate_regular_cnf(lengths, n, alpha, self == Generator::RegularBalanced, seed), None)
+            }
+        }
+    }
+}
+
+/// Distribution of the lengths of the clauses of a formula, as pairs of a
+/// length and the fraction of the clauses which have it
+#[derive(Clone, Debug, PartialEq)]
+pub struct Lengths(pub Vec<(u8, f64)>);
+
+impl Lengths {
+    /// Clauses of k literals
+    pub fn fixed(k: u8) -> Self {
+        Lengths(vec![(k, 1.)])
+    }
+
+    /// Clauses of k literals for a fraction p of them, and of 2 literals for
+    /// the others, as in (2+p)-SAT with k = 3
+    pub fn mixed(k: u8, p: f64) -> Self {
+        Lengths(vec![(2, 1. - p), (k, p)])
+    }
+
+    /// Largest length of the clauses
+    pub fn max(&self) -> u8 {
+        self.0.iter().filter(|&&(_, fraction)| fraction > 0.).map(|&(length, _)| length).max().unwrap_or(0)
+    }
+
+    /// Lengths of `count` clauses in random order, the number of clauses of
+    /// each length being as close as possible to its share
+    fn sample(&self, count: usize, rng: &mut dyn RngCore) -> Vec<u8> {
+        let total: f64 = self.0.iter().map(|&(_, fraction)| fraction).sum();
+        let shares: Vec<f64> = self.0.iter().map(|&(_, fraction)| fraction / total * count as f64).collect();
+        let mut counts: Vec<usize> = shares.iter().map(|share| share.floor() as usize).collect();
+        // Remaining clauses go to the largest remainders
+        let mut order: Vec<usize> = (0..shares.len()).collect();
+        order.sort_by(|&a, &b| (shares[b] - shares[b].floor()).total_cmp(&(shares[a] - shares[a].floor())));
+        for &idx in order.iter().cycle().take(count - counts.iter().sum::<usize>()) {
+            counts[idx] += 1;
+        }
+        let mut lengths: Vec<u8> = self.0.iter().zip(counts).flat_map(|(&(length, _), count)| vec![length; count]).collect();
+        // A single length consumes no random number, keeping the formulas of a given seed
+        if self.0.iter().filter(|&&(_, fraction)| fraction > 0.).count() > 1 {
+            lengths.shuffle(rng);
+        }
+        lengths
+    }
+}
+
+pub fn seeded_rng(seed: Option<u64>) -> Box<dyn RngCore> {
+    match seed {
         Some(val) => Box::new(StdRng::seed_from_u64(val)),
         None => Box::new(rand::thread_rng()),
-    };
+    }
+}
+
+/// k distinct variables drawn uniformly among n, in the order they were drawn
+pub fn random_variables(rng: &mut dyn RngCore, k: u8, n: u32) -> Vec<u32> {
+    let mut variables = vec![];
+    while variables.len() < k.into() {
+        let candidate = rng.gen_range(0..n);
+        if !variables.contains(&candidate) {
+            variables.push(candidate);
+        }
+    }
+    variables
+}
+
+/// Clause of k distinct variables drawn uniformly, with random signs
+fn random_clause(rng: &mut dyn RngCore, k: u8, n: u32) -> CNFClause {
+    let literals = random_variables(rng, k, n)
+        .into_iter()
+        .map(|variable| CNFLiteral {
+            negate: rng.gen_bool(0.5),
+            variable,
+        })
+        .collect();
+    CNFClause { literals }
+}
+
+/// Generate a uniform random k-SAT formula
+pub fn generate_cnf(k: u8, n: u32, alpha: f32, seed: Option<u64>) -> CNFFormula {
+    generate_mixed_cnf(&Lengths::fixed(k), n, alpha, seed)
+}
+
+/// Generate a uniform random formula whose clauses have lengths drawn from
+/// a distribution
+pub fn generate_mixed_cnf(lengths: &Lengths, n: u32, alpha: f32, seed: Option<u64>) -> CNFFormula {
+    assert!(n >= lengths.max().into());
+    let mut rng = seeded_rng(seed);
+    let clauses_cnt = (alpha * (n as f32)) as usize;
+    let clauses = lengths
+        .sample(clauses_cnt, &mut rng)
+        .into_iter()
+        .map(|k| random_clause(&mut rng, k, n))
+        .collect();
+    CNFFormula { clauses }
+}
+
+/// Weights of the clauses of quiet planting by number of true literals
+/// under the hidden assignment, the largest being 1
+///
+/// Clauses with fewer than k/2 true literals get weight 1, and those with
+/// more a weight λ such that each literal occurs as often in expectation
+/// whether it is true or false (for k = 3, weights 1, 1/2 and 1/2 for 1, 2
+/// and 3 true literals). Requires k ≥ 2.
+fn quiet_weights(k: u8) -> Vec<f64> {
+    let k = k as i32;
+    let binomial = |t: i32| (1..=t).fold(1., |acc, i| acc * (k - t + i) as f64 / i as f64);
+    // Excess of true over false literals among the clauses with t true literals
+    let excess = |t: i32| binomial(t) * (2 * t - k) as f64;
+    let deficit: f64 = -(1..=k).filter(|t| 2 * t <= k).map(excess).sum::<f64>();
+    let surplus: f64 = (1..=k).filter(|t| 2 * t > k).map(excess).sum();
+    let lambda = deficit / surplus;
+    let weights: Vec<f64> = (0..=k)
+        .map(|t| match t {
+            0 => 0.,
+            t if 2 * t <= k => 1.,
+            _ => lambda,
+        })
+        .collect();
+    let largest = weights.iter().copied().fold(0., f64::max);
+    weights.into_iter().map(|weight| weight / largest).collect()
+}
+
+/// Generate a random formula satisfied by a hidden assignment, which is
+/// returned as well
+///
+/// Uniform random clauses are drawn until enough of them are accepted: the
+/// clauses satisfied by the hidden assignment, or with `quiet`, each of them
+/// with a probability depending on its number of true literals (see
+/// `quiet_weights`), which hides the assignment from simple literal counts.
+pub fn generate_planted_cnf(lengths: &Lengths, n: u32, alpha: f32, quiet: bool, seed: Option<u64>) -> (CNFFormula, Vec<bool>) {
+    assert!(n >= lengths.max().into());
+    // An empty clause is never satisfied, and would be drawn forever
+    assert!(lengths.0.iter().all(|&(k, _)| k >= 1), "planted clauses require at least 1 literal");
+    let mut rng = seeded_rng(seed);
+    let planted: Vec<bool> = (0..n).map(|_| rng.gen_bool(0.5)).collect();
     let clauses_cnt = (alpha * (n as f32)) as usize;
-    let clauses = (0..clauses_cnt)
-        .map(|_| {
-            let mut variables = vec![];
-            while variables.len() < k.into() {
-                let candidate = rng.gen_range(0..n);
-                if !variables.contains(&candidate) {
-                    variables.push(candidate);
+    let mut clauses = Vec::with_capacity(clauses_cnt);
+    for k in lengths.sample(clauses_cnt, &mut rng) {
+        assert!(k >= 2 || !quiet, "quiet planting requires clauses of at least 2 literals");
+        let weights = if quiet {
+            quiet_weights(k)
+        } else {
+            (0..=k).map(|t| if t == 0 { 0. } else { 1. }).collect()
+        };
+        loop {
+            let clause = random_clause(&mut rng, k, n);
+            let satisfied = clause
+                .literals
+                .iter()
+                .filter(|literal| planted[literal.variable as usize] != literal.negate)
+                .count();
+            if rng.gen_bool(weights[satisfied]) {
+                clauses.push(clause);
+                break;
+            }
+        }
+    }
+    (CNFFormula { clauses }, planted)
+}
+
+/// Generate a regular random formula with the configuration model
+///
+/// Each variable gets the same number of slots (some of them one more, if
+/// the number of literals is not a multiple of n), which are shuffled and
+/// cut into clauses. With `balanced`, half of the slots of each variable
+/// are negated, and otherwise the signs are random. A clause drawing a
+/// variable twice has one of its duplicates swapped with the slot of
+/// another clause, swaps creating another duplicate being rejected, so that
+/// the degrees of the variables are kept. A configuration whose duplicates
+/// are not all removed after a number of swaps proportional to its size is
+/// rejected, and the slots are shuffled again.
+pub fn generate_regular_cnf(lengths: &Lengths, n: u32, alpha: f32, balanced: bool, seed: Option<u64>) -> CNFFormula {
+    assert!(n >= lengths.max().into());
+    let mut rng = seeded_rng(seed);
+    let clauses_cnt = (alpha * (n as f32)) as usize;
+    let lengths = lengths.sample(clauses_cnt, &mut rng);
+    // Clause of each slot, and first slot of each clause
+    let clause_of: Vec<usize> = lengths
+        .iter()
+        .enumerate()
+        .flat_map(|(clause, &k)| std::iter::repeat_n(clause, k as usize))
+        .collect();
+    let starts: Vec<usize> = lengths
+        .iter()
+        .scan(0, |start, &k| {
+            *start += k as usize;
+            Some(*start - k as usize)
+        })
+        .collect();
+    let slots_cnt = clause_of.len();
+
+    // Variables getting an extra slot are chosen at random
+    let mut variables: Vec<u32> = (0..n).collect();
+    variables.shuffle(&mut rng);
+    let mut slots: Vec<CNFLiteral> = Vec::with_capacity(slots_cnt);
+    for (idx, &variable) in variables.iter().enumerate() {
+        let degree = slots_cnt / n as usize + usize::from(idx < slots_cnt % n as usize);
+        for occurrence in 0..degree {
+            let negate = if !balanced || (degree % 2 == 1 && occurrence == degree - 1) {
+                rng.gen_bool(0.5)
+            } else {
+                occurrence % 2 == 1
+            };
+            slots.push(CNFLiteral { negate, variable });
+        }
+    }
+
+    let duplicate = |slots: &[CNFLiteral], slot: usize| {
+        let clause = clause_of[slot];
+        let range = starts[clause]..starts[clause] + lengths[clause] as usize;
+        range.filter(|&other| other != slot).any(|other| slots[other].variable == slots[slot].variable)
+    };
+    'configuration: loop {
+        slots.shuffle(&mut rng);
+        let mut attempts = 0;
+        for slot in 0..slots_cnt {
+            while duplicate(&slots, slot) {
+                attempts += 1;
+                if attempts >= 1000 * slots_cnt {
+                    continue 'configuration;
+                }
+                let other = rng.gen_range(0..slots_cnt);
+                if clause_of[other] == clause_of[slot] {
+                    continue;
+                }
+                slots.swap(slot, other);
+                if duplicate(&slots, slot) || duplicate(&slots, other) {
+                    slots.swap(slot, other);
                 }
             }
-            let literals = variables
-                .into_iter()
-                .map(|variable| CNFLiteral {
-                    negate: rng.gen_bool(0.5),
-                    variable,
-                })
-                .collect();
-            CNFClause { literals: literals }
+        }
+        break;
+    }
+
+    let clauses = starts
+        .iter()
+        .zip(lengths)
+        .map(|(&start, k)| CNFClause {
+            literals: slots[start..start + k as usize].to_vec(),
         })
         .collect();
-    CNFFormula { clauses: clauses }
+    CNFFormula { clauses }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::cnf::Evaluation;
 
     #[test]
     fn test_gen() {
@@ -46,4 +311,103 @@ mod tests {
             formula.to_string()
         );
     }
+
+    #[test]
+    fn test_sample_seed() {
+        let mut seeds: Vec<u64> = (0..10)
+            .flat_map(|alpha_idx| (0..10).map(move |sample_idx| sample_seed(42, alpha_idx, sample_idx)))
+            .collect();
+        assert_eq!(sample_seed(42, 3, 7), seeds[37]);
+        seeds.sort();
+        seeds.dedup();
+        assert_eq!(100, seeds.len());
+        assert_ne!(sample_seed(42, 0, 0), sample_seed(43, 0, 0));
+    }
+
+    #[test]
+    fn test_planted() {
+        for quiet in [false, true] {
+            let (formula, planted) = generate_planted_cnf(&Lengths::fixed(3), 50, 8., quiet, Some(42));
+            assert_eq!(400, formula.clauses.len());
+            let assignment: Vec<Option<bool>> = planted.iter().copied().map(Some).collect();
+            assert_eq!(Evaluation::Satisfied, formula.evaluate(&assignment));
+        }
+        assert_eq!(vec![0., 1., 0.5, 0.5], quiet_weights(3));
+        assert_eq!(vec![0., 1., 0.], quiet_weights(2));
+    }
+
+    #[test]
+    fn test_regular() {
+        for balanced in [false, true] {
+            let formula = generate_regular_cnf(&Lengths::fixed(3), 100, 4.5, balanced, Some(42));
+            assert_eq!(450, formula.clauses.len());
+            let mut occurrences = vec![[0u32, 0]; 100];
+            for clause in formula.clauses.iter() {
+                let mut variables: Vec<u32> = clause.literals.iter().map(|literal| literal.variable).collect();
+                variables.sort();
+                variables.dedup();
+                assert_eq!(3, variables.len());
+                for literal in clause.literals.iter() {
+                    occurrences[literal.variable as usize][literal.negate as usize] += 1;
+                }
+            }
+            // 1350 literals, i.e. 13 or 14 occurrences of each variable
+            assert!(occurrences.iter().all(|[positive, negative]| (13..=14).contains(&(positive + negative))));
+            assert_eq!(50, occurrences.iter().filter(|[positive, negative]| positive + negative == 14).count());
+            if balanced {
+                assert!(occurrences.iter().all(|[positive, negative]| positive.abs_diff(*negative) <= 1));
+            }
+        }
+    }
+
+    #[test]
+    fn test_regular_restart() {
+        // Every clause holds all the variables, which single swaps cannot
+        // always reach: some configurations are shuffled again (e.g. for seed 65)
+        for seed in 0..100 {
+            let formula = generate_regular_cnf(&Lengths::fixed(3), 3, 2., false, Some(seed));
+            for clause in formula.clauses.iter() {
+                let mut variables: Vec<u32> = clause.literals.iter().map(|literal| literal.variable).collect();
+                variables.sort();
+                assert_eq!(vec![0, 1, 2], variables);
+            }
+        }
+    }
+
+    #[test]
+    fn test_mixed() {
+        let lengths = Lengths::mixed(3, 0.4);
+        assert_eq!(3, lengths.max());
+        assert_eq!(2, Lengths::mixed(3, 0.).max());
+        let formula = generate_mixed_cnf(&lengths, 50, 3., Some(42));
+        let count = |k| formula.clauses.iter().filter(|clause| clause.literals.len() == k).count();
+        assert_eq!((90, 60), (count(2), count(3)));
+        // Fractions are rounded to the closest numbers of clauses
+        let mut rng = seeded_rng(Some(1));
+        let lengths = Lengths(vec![(2, 0.5), (3, 0.3), (4, 0.2)]).sample(7, &mut rng);
+        assert_eq!([4, 2, 1], [2, 3, 4].map(|k| lengths.iter().filter(|&&length| length == k).count()));
+
+        let (formula, planted) = generate_planted_cnf(&Lengths::mixed(3, 0.5), 20, 5., true, Some(42));
+        let assignment: Vec<Option<bool>> = planted.iter().copied().map(Some).collect();
+        assert_eq!(Evaluation::Satisfied, formula.evaluate(&assignment));
+        let formula = generate_regular_cnf(&Lengths::mixed(3, 0.5), 20, 5., false, Some(42));
+        assert_eq!(250, formula.clauses.iter().map(|clause| clause.literals.len()).sum::<usize>());
+    }
+
+    #[test]
+    fn test_quiet_planting() {
+        // Literals agree with the hidden assignment as often as they disagree
+        let count = |quiet| {
+            let (formula, planted) = generate_planted_cnf(&Lengths::fixed(3), 1000, 20., quiet, Some(7));
+            let agreeing = formula
+                .clauses
+                .iter()
+                .flat_map(|clause| clause.literals.iter())
+                .filter(|literal| planted[literal.variable as usize] != literal.negate)
+                .count();
+            agreeing as f64 / (3. * formula.clauses.len() as f64)
+        };
+        assert!((count(true) - 0.5).abs() < 0.01);
+        assert!((count(false) - 4. / 7.).abs() < 0.01);
+    }
 }
@@ -0,0 +1,727 @@
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+use crate::names::{parse_value, value_name};
+use crate::problem::Problem;
+use crate::rand_cnf::Generator;
+use crate::stats::Interval;
+
+/// Error raised when reading malformed results
+#[derive(Debug, PartialEq)]
+pub struct ResultsError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl ResultsError {
+    fn new(line: usize, message: impl Into<String>) -> Self {
+        ResultsError {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ResultsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl Error for ResultsError {}
+
+/// Configuration of a sweep, recorded along with its results
+#[derive(Clone, Debug, PartialEq)]
+pub struct SweepConfig {
+    pub k: u8,
+    pub n: u32,
+    /// Master seed from which the samples were generated
+    pub seed: u64,
+    /// Master seeds of the other sweeps merged into these results, if any
+    pub merged_seeds: Vec<u64>,
+    /// Kind of constraints of the formulas
+    pub problem: Problem,
+    /// Distribution of the formulas
+    pub generator: Generator,
+    /// Fraction of the clauses of length k in mixed formulas, whose other
+    /// clauses have length 2, if p is fixed
+    pub p: Option<f64>,
+    /// Ratio of clauses to variables if it is fixed, p being swept instead
+    pub alpha: Option<f32>,
+    /// Bounds and number of steps of the grid of the swept parameter
+    pub alpha_start: f32,
+    pub alpha_end: f32,
+    pub alpha_steps: usize,
+    /// Maximum number of times the grid was refined in adaptive mode, `None`
+    /// if the sweep was not adaptive
+    pub refine: Option<u32>,
+    /// Whether the symmetry between colours was broken in graph colouring
+    pub symmetry_breaking: bool,
+    pub solver: String,
+    /// Measure of the cost of the solver, if any
+    pub cost: Option<String>,
+    /// Method and level of the confidence intervals
+    pub interval: Interval,
+    pub confidence: f64,
+    /// Version of satisfaction which ran the sweep
+    pub version: String,
+}
+
+/// Results of a sweep for one value of alpha
+#[derive(Clone, Debug, PartialEq)]
+pub struct SweepPoint {
+    /// Value of alpha, of p if alpha is fixed, or of the average degree c
+    /// for graph colouring
+    pub alpha: f32,
+    /// Index of the value on the finest grid of the sweep, from which the
+    /// seeds of its samples are derived (see the `generate` subcommand)
+    pub alpha_index: usize,
+    pub samples: u32,
+    /// Number of satisfiable samples
+    pub satisfiable: u32,
+    /// Bounds of the confidence interval of the share of satisfiable formulas
+    pub lower: f64,
+    pub upper: f64,
+    /// Mean time taken by the solver on a sample, in seconds
+    pub mean_time: f64,
+    pub median_cost: Option<f64>,
+    pub mean_cost: Option<f64>,
+}
+
+impl SweepConfig {
+    /// Name of the swept parameter, `alpha`, `p`, or `c` for graph colouring
+    pub fn parameter(&self) -> &'static str {
+        if self.alpha.is_some() {
+            "p"
+        } else if self.problem == Problem::Colouring {
+            "c"
+        } else {
+            "alpha"
+        }
+    }
+}
+
+impl SweepPoint {
+    /// Share of satisfiable formulas among the samples
+    pub fn probability(&self) -> f64 {
+        self.satisfiable as f64 / self.samples as f64
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SweepResults {
+    pub config: SweepConfig,
+    pub points: Vec<SweepPoint>,
+}
+
+/// Columns of the CSV files after the swept parameter
+const CSV_COLUMNS: &str = "samples,satisfiable,probability,lower,upper,mean_time,median_cost,mean_cost,alpha_index";
+
+fn csv_columns(parameter: &str) -> String {
+    format!("{},{}", parameter, CSV_COLUMNS)
+}
+
+impl SweepResults {
+    /// Parse results written by `write_csv`
+    ///
+    /// Results without `interval` and `confidence` lines are assumed to
+    /// hold 95% Wilson score intervals, those without `problem` and
+    /// `generator` lines uniform random k-SAT formulas, and those without
+    /// a grid the default grid of 100 steps of alpha from 0 to 10. The first column is
+    /// `p` rather than `alpha` if an `alpha` line fixes the ratio of clauses
+    /// to variables, and `c` for graph colouring.
+    pub fn parse_csv(input: &str) -> Result<Self, ResultsError> {
+        let mut problem = Problem::Sat;
+        let mut generator = Generator::Uniform;
+        let mut p = None;
+        let mut alpha = None;
+        let mut alpha_start = 0.;
+        let mut alpha_end = 10.;
+        let mut alpha_steps = 100;
+        let mut refine = None;
+        let mut symmetry_breaking = false;
+        let mut parameter = "alpha";
+        let mut interval = Interval::Wilson;
+        let mut confidence = 0.95;
+        let mut version = None;
+        let mut k = None;
+        let mut n = None;
+        let mut seed = None;
+        let mut merged_seeds = vec![];
+        let mut solver = None;
+        let mut cost = None;
+        let mut header = None;
+        let mut points = vec![];
+        let mut last_line = 0;
+
+        for (idx, line) in input.lines().enumerate() {
+            let line_nb = idx + 1;
+            last_line = line_nb;
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(comment) = line.strip_prefix('#') {
+                let Some((key, value)) = comment.split_once(':') else {
+                    continue;
+                };
+                let value = value.trim();
+                match key.trim() {
+                    "version" => version = Some(value.to_string()),
+                    "k" => k = Some(parse_field(line_nb, "k", value)?),
+                    "n" => n = Some(parse_field(line_nb, "n", value)?),
+                    "seed" => seed = Some(parse_field(line_nb, "seed", value)?),
+                    "merged seeds" => {
+                        merged_seeds = value
+                            .split(',')
+                            .map(|seed| parse_field(line_nb, "seed", seed.trim()))
+                            .collect::<Result<_, _>>()?
+                    }
+                    "problem" => {
+                        problem = parse_value(value)
+                            .ok_or_else(|| ResultsError::new(line_nb, format!("unknown problem `{}`", value)))?
+                    }
+                    "generator" => {
+                        generator = parse_value(value)
+                            .ok_or_else(|| ResultsError::new(line_nb, format!("unknown generator `{}`", value)))?
+                    }
+                    "p" => p = Some(parse_field(line_nb, "p", value)?),
+                    "alpha" => alpha = Some(parse_field(line_nb, "alpha", value)?),
+                    "alpha start" => alpha_start = parse_field(line_nb, "alpha start", value)?,
+                    "alpha end" => alpha_end = parse_field(line_nb, "alpha end", value)?,
+                    "alpha steps" => alpha_steps = parse_field(line_nb, "alpha steps", value)?,
+                    "refine" => refine = Some(parse_field(line_nb, "refine", value)?),
+                    "symmetry breaking" => symmetry_breaking = parse_field(line_nb, "symmetry breaking", value)?,
+                    "solver" => solver = Some(value.to_string()),
+                    "cost" => cost = Some(value.to_string()),
+                    "interval" => {
+                        interval = parse_value(value)
+                            .ok_or_else(|| ResultsError::new(line_nb, format!("unknown interval `{}`", value)))?
+                    }
+                    "confidence" => confidence = parse_field(line_nb, "confidence", value)?,
+                    _ => (),
+                }
+            } else if header.is_none() {
+                parameter = match line.split_once(',') {
+                    Some(("alpha", CSV_COLUMNS)) => "alpha",
+                    Some(("p", CSV_COLUMNS)) => "p",
+                    Some(("c", CSV_COLUMNS)) => "c",
+                    _ => {
+                        return Err(ResultsError::new(
+                            line_nb,
+                            format!(
+                                "expected the columns `{}`, `{}` or `{}`",
+                                csv_columns("alpha"),
+                                csv_columns("p"),
+                                csv_columns("c")
+                            ),
+                        ))
+                    }
+                };
+                header = Some(line_nb);
+            } else {
+                let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+                if fields.len() != 10 {
+                    return Err(ResultsError::new(line_nb, format!("expected 10 fields, found {}", fields.len())));
+                }
+                let optional = |name, value: &str| match value {
+                    "" => Ok(None),
+                    value => parse_field(line_nb, name, value).map(Some),
+                };
+                points.push(SweepPoint {
+                    alpha: parse_field(line_nb, parameter, fields[0])?,
+                    samples: parse_field(line_nb, "samples", fields[1])?,
+                    satisfiable: parse_field(line_nb, "satisfiable", fields[2])?,
+                    lower: parse_field(line_nb, "lower", fields[4])?,
+                    upper: parse_field(line_nb, "upper", fields[5])?,
+                    mean_time: parse_field(line_nb, "mean_time", fields[6])?,
+                    median_cost: optional("median_cost", fields[7])?,
+                    mean_cost: optional("mean_cost", fields[8])?,
+                    alpha_index: parse_field(line_nb, "alpha_index", fields[9])?,
+                });
+            }
+        }
+
+        let missing = |name| ResultsError::new(last_line.max(1), format!("missing `# {}:` line", name));
+        let Some(header) = header else {
+            return Err(ResultsError::new(last_line.max(1), "missing column names"));
+        };
+        if parameter == "p" && alpha.is_none() {
+            return Err(missing("alpha"));
+        }
+        if (parameter == "c") != (problem == Problem::Colouring) {
+            return Err(ResultsError::new(
+                header,
+                format!("the first column is `c` for graph colouring only, not for {}", value_name(&problem)),
+            ));
+        }
+        Ok(SweepResults {
+            config: SweepConfig {
+                k: k.ok_or_else(|| missing("k"))?,
+                n: n.ok_or_else(|| missing("n"))?,
+                seed: seed.ok_or_else(|| missing("seed"))?,
+                merged_seeds,
+                problem,
+                generator,
+                p,
+                alpha,
+                alpha_start,
+                alpha_end,
+                alpha_steps,
+                refine,
+                symmetry_breaking,
+                solver: solver.ok_or_else(|| missing("solver"))?,
+                cost,
+                interval,
+                confidence,
+                version: version.ok_or_else(|| missing("version"))?,
+            },
+            points,
+        })
+    }
+
+    /// Merge the points of several sweeps with the same problem, k, n and generator, adding up
+    /// the samples of equal values of alpha (the configuration of the first
+    /// sweep is kept and used to recompute the confidence intervals, costs
+    /// are dropped unless a single sweep is given)
+    ///
+    /// Sweeps with the same master seed have the same formulas, and cannot
+    /// be merged. The seeds of the other sweeps are recorded in `merged_seeds`.
+    /// The sweeps must share their grid of alpha, so that the index of each
+    /// point holds for all the seeds.
+    pub fn merge(mut results: Vec<SweepResults>) -> Result<SweepResults, String> {
+        let mut merged = results.remove(0);
+        let (interval, confidence) = (merged.config.interval, merged.config.confidence);
+        for other in results {
+            let label = |config: &SweepConfig| config.problem.label(&config.k.to_string());
+            if (other.config.problem, other.config.k, other.config.n)
+                != (merged.config.problem, merged.config.k, merged.config.n)
+            {
+                return Err(format!(
+                    "cannot merge the results of {} with N={} and {} with N={}",
+                    label(&merged.config),
+                    merged.config.n,
+                    label(&other.config),
+                    other.config.n
+                ));
+            }
+            if (other.config.p, other.config.alpha) != (merged.config.p, merged.config.alpha) {
+                return Err("cannot merge the results of sweeps with different fixed values of p or alpha".to_string());
+            }
+            if other.config.generator != merged.config.generator {
+                return Err(format!(
+                    "cannot merge the results of {} and {} formulas",
+                    value_name(&merged.config.generator),
+                    value_name(&other.config.generator)
+                ));
+            }
+            let grid = |config: &SweepConfig| (config.alpha_start, config.alpha_end, config.alpha_steps, config.refine);
+            if grid(&other.config) != grid(&merged.config) {
+                return Err("cannot merge the results of sweeps with different grids of alpha".to_string());
+            }
+            let seeds = |config: &SweepConfig| std::iter::once(config.seed).chain(config.merged_seeds.clone());
+            if let Some(seed) = seeds(&other.config).find(|&seed| seeds(&merged.config).any(|other| other == seed)) {
+                return Err(format!("cannot merge the results of sweeps with the same seed {}", seed));
+            }
+            merged.config.merged_seeds.extend(seeds(&other.config));
+            merged.config.cost = None;
+            for point in merged.points.iter_mut() {
+                point.median_cost = None;
+                point.mean_cost = None;
+            }
+            for point in other.points {
+                match merged.points.iter_mut().find(|merged| merged.alpha == point.alpha) {
+                    Some(merged) => {
+                        let samples = merged.samples + point.samples;
+                        merged.mean_time = (merged.mean_time * merged.samples as f64
+                            + point.mean_time * point.samples as f64)
+                            / samples as f64;
+                        merged.samples = samples;
+                        merged.satisfiable += point.satisfiable;
+                        (merged.lower, merged.upper) =
+                            interval.bounds(merged.satisfiable, samples, confidence);
+                    }
+                    None => merged.points.push(SweepPoint {
+                        median_cost: None,
+                        mean_cost: None,
+                        ..point
+                    }),
+                }
+            }
+        }
+        merged.points.sort_by(|a, b| a.alpha.total_cmp(&b.alpha));
+        Ok(merged)
+    }
+
+    /// Write the results in CSV format, with one row per value of alpha
+    ///
+    /// The configuration is written first as `# key: value` comment lines,
+    /// missing costs are left empty.
+    pub fn write_csv(&self, writer: &mut impl Write) -> io::Result<()> {
+        let config = &self.config;
+        writeln!(writer, "# version: {}", config.version)?;
+        writeln!(writer, "# k: {}", config.k)?;
+        writeln!(writer, "# n: {}", config.n)?;
+        writeln!(writer, "# seed: {}", config.seed)?;
+        if !config.merged_seeds.is_empty() {
+            let seeds: Vec<String> = config.merged_seeds.iter().map(u64::to_string).collect();
+            writeln!(writer, "# merged seeds: {}", seeds.join(","))?;
+        }
+        writeln!(writer, "# problem: {}", value_name(&config.problem))?;
+        writeln!(writer, "# generator: {}", value_name(&config.generator))?;
+        if let Some(p) = config.p {
+            writeln!(writer, "# p: {}", p)?;
+        }
+        if let Some(alpha) = config.alpha {
+            writeln!(writer, "# alpha: {}", alpha)?;
+        }
+        writeln!(writer, "# alpha start: {}", config.alpha_start)?;
+        writeln!(writer, "# alpha end: {}", config.alpha_end)?;
+        writeln!(writer, "# alpha steps: {}", config.alpha_steps)?;
+        if let Some(refine) = config.refine {
+            writeln!(writer, "# refine: {}", refine)?;
+        }
+        if config.symmetry_breaking {
+            writeln!(writer, "# symmetry breaking: true")?;
+        }
+        writeln!(writer, "# solver: {}", config.solver)?;
+        if let Some(cost) = &config.cost {
+            writeln!(writer, "# cost: {}", cost)?;
+        }
+        writeln!(writer, "# interval: {}", value_name(&config.interval))?;
+        writeln!(writer, "# confidence: {}", config.confidence)?;
+        writeln!(writer, "{}", csv_columns(config.parameter()))?;
+        for point in self.points.iter() {
+            let optional = |value: Option<f64>| value.map(|value| value.to_string()).unwrap_or_default();
+            writeln!(
+                writer,
+                "{},{},{},{},{},{},{},{},{},{}",
+                point.alpha,
+                point.samples,
+                point.satisfiable,
+                point.probability(),
+                point.lower,
+                point.upper,
+                point.mean_time,
+                optional(point.median_cost),
+                optional(point.mean_cost),
+                point.alpha_index
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Write the results as a JSON object with a `config` object and
+    /// a `points` array (missing or undefined numbers are `null`)
+    pub fn write_json(&self, writer: &mut impl Write) -> io::Result<()> {
+        let config = &self.config;
+        writeln!(writer, "{{")?;
+        writeln!(writer, "  \"config\": {{")?;
+        writeln!(writer, "    \"version\": {},", json_string(&config.version))?;
+        writeln!(writer, "    \"k\": {},", config.k)?;
+        writeln!(writer, "    \"n\": {},", config.n)?;
+        writeln!(writer, "    \"seed\": {},", config.seed)?;
+        let seeds: Vec<String> = config.merged_seeds.iter().map(u64::to_string).collect();
+        writeln!(writer, "    \"merged_seeds\": [{}],", seeds.join(", "))?;
+        writeln!(writer, "    \"problem\": {},", json_string(&value_name(&config.problem)))?;
+        writeln!(writer, "    \"generator\": {},", json_string(&value_name(&config.generator)))?;
+        writeln!(writer, "    \"p\": {},", config.p.map(json_number).unwrap_or("null".to_string()))?;
+        writeln!(
+            writer,
+            "    \"alpha\": {},",
+            config.alpha.map(|alpha| alpha.to_string()).unwrap_or("null".to_string())
+        )?;
+        writeln!(writer, "    \"alpha_start\": {},", config.alpha_start)?;
+        writeln!(writer, "    \"alpha_end\": {},", config.alpha_end)?;
+        writeln!(writer, "    \"alpha_steps\": {},", config.alpha_steps)?;
+        writeln!(
+            writer,
+            "    \"refine\": {},",
+            config.refine.map(|refine| refine.to_string()).unwrap_or("null".to_string())
+        )?;
+        writeln!(writer, "    \"symmetry_breaking\": {},", config.symmetry_breaking)?;
+        writeln!(writer, "    \"solver\": {},", json_string(&config.solver))?;
+        writeln!(
+            writer,
+            "    \"cost\": {},",
+            config.cost.as_deref().map(json_string).unwrap_or("null".to_string())
+        )?;
+        writeln!(writer, "    \"interval\": {},", json_string(&value_name(&config.interval)))?;
+        writeln!(writer, "    \"confidence\": {}", json_number(config.confidence))?;
+        writeln!(writer, "  }},")?;
+        writeln!(writer, "  \"points\": [")?;
+        for (idx, point) in self.points.iter().enumerate() {
+            writeln!(
+                writer,
+                "    {{\"{}\": {}, \"samples\": {}, \"satisfiable\": {}, \"probability\": {}, \
+                 \"lower\": {}, \"upper\": {}, \"mean_time\": {}, \"median_cost\": {}, \"mean_cost\": {}, \
+                 \"alpha_index\": {}}}{}",
+                config.parameter(),
+                point.alpha,
+                point.samples,
+                point.satisfiable,
+                json_number(point.probability()),
+                json_number(point.lower),
+                json_number(point.upper),
+                json_number(point.mean_time),
+                point.median_cost.map(json_number).unwrap_or("null".to_string()),
+                point.mean_cost.map(json_number).unwrap_or("null".to_string()),
+                point.alpha_index,
+                if idx + 1 < self.points.len() { "," } else { "" }
+            )?;
+        }
+        writeln!(writer, "  ]")?;
+        writeln!(writer, "}}")
+    }
+}
+
+fn parse_field<T: FromStr>(line: usize, name: &str, value: &str) -> Result<T, ResultsError> {
+    value
+        .parse()
+        .map_err(|_| ResultsError::new(line, format!("invalid {} `{}`", name, value)))
+}
+
+fn json_number(value: f64) -> String {
+    if value.is_finite() {
+        value.to_string()
+    } else {
+        "null".to_string()
+    }
+}
+
+fn json_string(value: &str) -> String {
+    let mut escaped = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> SweepResults {
+        SweepResults {
+            config: SweepConfig {
+                k: 3,
+                n: 20,
+                seed: 42,
+                merged_seeds: vec![],
+                problem: Problem::Sat,
+                generator: Generator::Uniform,
+                p: None,
+                alpha: None,
+                alpha_start: 3.,
+                alpha_end: 5.,
+                alpha_steps: 8,
+                refine: None,
+                symmetry_breaking: false,
+                solver: "dpll".to_string(),
+                cost: Some("decisions".to_string()),
+                interval: Interval::Wilson,
+                confidence: 0.95,
+                version: "0.1.0".to_string(),
+            },
+            points: vec![
+                SweepPoint {
+                    alpha: 4.,
+                    alpha_index: 4,
+                    samples: 4,
+                    satisfiable: 3,
+                    lower: 0.3,
+                    upper: 0.95,
+                    mean_time: 0.001,
+                    median_cost: Some(7.5),
+                    mean_cost: Some(8.),
+                },
+                SweepPoint {
+                    alpha: 4.26,
+                    alpha_index: 5,
+                    samples: 4,
+                    satisfiable: 0,
+                    lower: 0.,
+                    upper: 0.5,
+                    mean_time: 0.002,
+                    median_cost: None,
+                    mean_cost: None,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_write_csv() {
+        let mut output = vec![];
+        example().write_csv(&mut output).unwrap();
+        assert_eq!(
+            "# version: 0.1.0\n# k: 3\n# n: 20\n# seed: 42\n# problem: sat\n# generator: uniform\n\
+             # alpha start: 3\n# alpha end: 5\n# alpha steps: 8\n# solver: dpll\n# cost: decisions\n\
+             # interval: wilson\n# confidence: 0.95\n\
+             alpha,samples,satisfiable,probability,lower,upper,mean_time,median_cost,mean_cost,alpha_index\n\
+             4,4,3,0.75,0.3,0.95,0.001,7.5,8,4\n\
+             4.26,4,0,0,0,0.5,0.002,,,5\n",
+            String::from_utf8(output).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_csv() {
+        let results = example();
+        let mut output = vec![];
+        results.write_csv(&mut output).unwrap();
+        let parsed = SweepResults::parse_csv(&String::from_utf8(output).unwrap()).unwrap();
+        assert_eq!(results, parsed);
+
+        let input = format!("# version: 0.1.0\n# k: 3\n# n: 20\n# seed: 1\n# solver: dpll\n{}\n", csv_columns("alpha"));
+        let parsed = SweepResults::parse_csv(&input).unwrap();
+        assert_eq!((Interval::Wilson, 0.95), (parsed.config.interval, parsed.config.confidence));
+        assert_eq!((Problem::Sat, Generator::Uniform), (parsed.config.problem, parsed.config.generator));
+        let config = &parsed.config;
+        assert_eq!((0., 10., 100, None), (config.alpha_start, config.alpha_end, config.alpha_steps, config.refine));
+
+        // Adaptive sweeps of graph colouring with symmetry breaking
+        let mut results = example();
+        results.config.refine = Some(4);
+        results.config.symmetry_breaking = true;
+        let mut output = vec![];
+        results.write_csv(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("# refine: 4\n# symmetry breaking: true\n"));
+        assert_eq!(results, SweepResults::parse_csv(&output).unwrap());
+
+        // Sweeps of p at a fixed alpha
+        let mut results = example();
+        results.config.alpha = Some(2.5);
+        let mut output = vec![];
+        results.write_csv(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("# alpha: 2.5\n"));
+        assert!(output.contains(&csv_columns("p")));
+        assert_eq!(results, SweepResults::parse_csv(&output).unwrap());
+        assert_eq!(
+            Err(ResultsError::new(1, "missing `# alpha:` line")),
+            SweepResults::parse_csv(&format!("{}\n", csv_columns("p")))
+        );
+
+        // Sweeps of the average degree in graph colouring
+        let mut results = example();
+        results.config.problem = Problem::Colouring;
+        let mut output = vec![];
+        results.write_csv(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains(&csv_columns("c")));
+        assert_eq!(results, SweepResults::parse_csv(&output).unwrap());
+        assert_eq!(
+            Err(ResultsError::new(2, "the first column is `c` for graph colouring only, not for sat")),
+            SweepResults::parse_csv(&format!("# version: 0.1.0\n{}\n", csv_columns("c")))
+        );
+        assert_eq!(
+            Err(ResultsError::new(1, "unknown interval `exact`")),
+            SweepResults::parse_csv("# interval: exact\n")
+        );
+
+        assert_eq!(
+            Err(ResultsError::new(2, "invalid k `x`")),
+            SweepResults::parse_csv("# version: 0.1.0\n# k: x\n")
+        );
+        assert_eq!(
+            Err(ResultsError::new(2, "expected 10 fields, found 2")),
+            SweepResults::parse_csv(&format!("{}\n1,2\n", csv_columns("alpha")))
+        );
+        assert_eq!(
+            Err(ResultsError::new(1, "missing `# k:` line")),
+            SweepResults::parse_csv(&format!("{}\n", csv_columns("alpha")))
+        );
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut other = example();
+        other.config.seed = 43;
+        other.points[0].satisfiable = 1;
+        other.points[0].mean_time = 0.003;
+        other.points[1].alpha = 5.;
+        other.points[1].alpha_index = 8;
+        let merged = SweepResults::merge(vec![example(), other]).unwrap();
+        assert_eq!((42, vec![43]), (merged.config.seed, merged.config.merged_seeds.clone()));
+        assert_eq!(None, merged.config.cost);
+        assert_eq!(vec![4., 4.26, 5.], merged.points.iter().map(|point| point.alpha).collect::<Vec<_>>());
+        assert_eq!((8, 4), (merged.points[0].samples, merged.points[0].satisfiable));
+        assert_eq!(0.002, merged.points[0].mean_time);
+        assert_eq!(Interval::Wilson.bounds(4, 8, 0.95), (merged.points[0].lower, merged.points[0].upper));
+        assert_eq!(None, merged.points[0].median_cost);
+
+        // The merged seeds are written, and read back
+        let mut output = vec![];
+        merged.write_csv(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("# seed: 42\n# merged seeds: 43\n"));
+        assert_eq!(merged, SweepResults::parse_csv(&output).unwrap());
+
+        // The same formulas are not counted twice
+        assert_eq!(
+            Err("cannot merge the results of sweeps with the same seed 42".to_string()),
+            SweepResults::merge(vec![example(), example()])
+        );
+        let mut other = example();
+        other.config.seed = 44;
+        other.config.merged_seeds = vec![43];
+        assert_eq!(
+            Err("cannot merge the results of sweeps with the same seed 43".to_string()),
+            SweepResults::merge(vec![merged, other])
+        );
+
+        let mut other = example();
+        other.config.n = 30;
+        assert!(SweepResults::merge(vec![example(), other]).is_err());
+        let mut other = example();
+        other.config.problem = Problem::XorSat;
+        assert_eq!(
+            Err("cannot merge the results of 3-SAT with N=20 and 3-XORSAT with N=20".to_string()),
+            SweepResults::merge(vec![example(), other])
+        );
+        let mut other = example();
+        other.config.refine = Some(2);
+        other.config.seed = 43;
+        assert_eq!(
+            Err("cannot merge the results of sweeps with different grids of alpha".to_string()),
+            SweepResults::merge(vec![example(), other])
+        );
+        let mut other = example();
+        other.config.generator = Generator::Planted;
+        assert_eq!(
+            Err("cannot merge the results of uniform and planted formulas".to_string()),
+            SweepResults::merge(vec![example(), other])
+        );
+    }
+
+    #[test]
+    fn test_write_json() {
+        let mut output = vec![];
+        example().write_json(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains(
+            "\"seed\": 42,\n    \"merged_seeds\": [],\n    \"problem\": \"sat\",\n    \"generator\": \"uniform\",\n"
+        ));
+        assert!(output.contains(
+            "\"alpha_start\": 3,\n    \"alpha_end\": 5,\n    \"alpha_steps\": 8,\n    \"refine\": null,\n    \"symmetry_breaking\": false,\n"
+        ));
+        assert!(output.contains("\"cost\": \"decisions\",\n"));
+        assert!(output.contains("\"interval\": \"wilson\",\n    \"confidence\": 0.95\n"));
+        assert!(output.contains(
+            "{\"alpha\": 4.26, \"samples\": 4, \"satisfiable\": 0, \"probability\": 0, \"lower\": 0, \
+             \"upper\": 0.5, \"mean_time\": 0.002, \"median_cost\": null, \"mean_cost\": null, \"alpha_index\": 5}\n"
+        ));
+        assert_eq!("\"a\\\"b\\\\c\\u000a\"", json_string("a\"b\\c\n"));
+        assert_eq!("null", json_number(f64::NAN));
+    }
+}
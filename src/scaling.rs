@@ -0,0 +1,246 @@
+/// Share of satisfiable formulas against alpha for a number n of variables
+pub struct Curve {
+    pub n: u32,
+    /// Values of alpha in increasing order, with the share of satisfiable
+    /// formulas and its standard error
+    pub points: Vec<(f64, f64, f64)>,
+}
+
+/// Threshold and critical exponent collapsing the curves of several sizes
+/// onto a single master curve, with rough errors
+#[derive(Clone, Debug, PartialEq)]
+pub struct Collapse {
+    pub alpha_c: f64,
+    pub alpha_c_error: f64,
+    pub nu: f64,
+    pub nu_error: f64,
+    /// Quality S of the collapse (close to 1 if the curves agree within errors)
+    pub quality: f64,
+}
+
+/// Range of shares of satisfiable formulas considered part of the transition
+const BAND: (f64, f64) = (0.05, 0.95);
+
+/// Range of the critical exponent ν searched
+const NU_RANGE: (f64, f64) = (0.1, 10.);
+
+/// Scaling variable (α - α_c)·n^(1/ν) of the finite-size scaling hypothesis
+pub fn scaling_variable(alpha: f64, alpha_c: f64, nu: f64, n: u32) -> f64 {
+    (alpha - alpha_c) * (n as f64).powf(1. / nu)
+}
+
+/// Quality of the collapse of the curves for given α_c and ν, or `None` if
+/// the rescaled curves barely overlap
+///
+/// Following Houdayer and Hartmann (Phys. Rev. B 70, 014418), every point
+/// of a curve in the transition is compared to the other curves, linearly
+/// interpolated at the same value of the scaling variable where they are
+/// defined: S is the mean of the squared differences divided by their
+/// variances. As S only averages over the pairs of points which overlap,
+/// at least half of the comparisons of the points of each curve must be
+/// possible, so that parameters pulling the curves apart cannot reach a
+/// small S on a few pairs.
+pub fn collapse_quality(curves: &[Curve], alpha_c: f64, nu: f64) -> Option<f64> {
+    let rescaled: Vec<Vec<(f64, f64, f64)>> = curves
+        .iter()
+        .map(|curve| {
+            curve
+                .points
+                .iter()
+                .map(|&(alpha, share, error)| (scaling_variable(alpha, alpha_c, nu, curve.n), share, error))
+                .collect()
+        })
+        .collect();
+    let (mut sum, mut count) = (0., 0);
+    for (i, points) in rescaled.iter().enumerate() {
+        let (mut compared, mut pairs) = (0, 0);
+        for &(x, share, error) in points.iter().filter(|point| point.1 >= BAND.0 && point.1 <= BAND.1) {
+            for (_, other) in rescaled.iter().enumerate().filter(|&(j, _)| j != i) {
+                pairs += 1;
+                let Some(segment) = other.windows(2).find(|pair| pair[0].0 <= x && x <= pair[1].0) else {
+                    continue;
+                };
+                let (left, right) = (segment[0], segment[1]);
+                let t = if right.0 > left.0 { (x - left.0) / (right.0 - left.0) } else { 0. };
+                let other_share = left.1 + t * (right.1 - left.1);
+                let other_error = left.2 + t * (right.2 - left.2);
+                sum += (share - other_share).powi(2) / (error * error + other_error * other_error).max(1e-12);
+                compared += 1;
+            }
+        }
+        if 2 * compared < pairs {
+            return None;
+        }
+        count += compared;
+    }
+    (count > 0).then(|| sum / count as f64)
+}
+
+/// Search for the α_c and ν minimizing the quality S of the collapse
+///
+/// α_c is searched among the values of alpha where some curve is in the
+/// transition, and ν between 0.1 and 10, on a grid which is repeatedly
+/// narrowed around its best point and kept within these bounds. The error
+/// of each parameter is the deviation (the other one being fixed) which
+/// raises S by one, a rough estimate since the terms of S are not
+/// independent. `None` is returned if no curve goes through the transition.
+pub fn collapse(curves: &[Curve]) -> Option<Collapse> {
+    let transition = curves
+        .iter()
+        .flat_map(|curve| curve.points.iter())
+        .filter(|point| point.1 >= BAND.0 && point.1 <= BAND.1)
+        .map(|point| point.0);
+    let low = transition.clone().fold(f64::INFINITY, f64::min);
+    let high = transition.fold(f64::NEG_INFINITY, f64::max);
+    if !low.is_finite() || curves.len() < 2 {
+        return None;
+    }
+
+    // ν is searched on a logarithmic scale
+    let quality = |alpha_c: f64, log_nu: f64| collapse_quality(curves, alpha_c, log_nu.exp()).unwrap_or(f64::INFINITY);
+    let log_nu_range = (NU_RANGE.0.ln(), NU_RANGE.1.ln());
+    let mut center = ((low + high) / 2., 0.);
+    let mut span = (((high - low) / 2.).max(1e-3), 10f64.ln());
+    let mut best = f64::INFINITY;
+    for _ in 0..20 {
+        let mut next = center;
+        for i in -5..=5 {
+            for j in -5..=5 {
+                let point = (
+                    (center.0 + span.0 * i as f64 / 5.).clamp(low, high),
+                    (center.1 + span.1 * j as f64 / 5.).clamp(log_nu_range.0, log_nu_range.1),
+                );
+                let value = quality(point.0, point.1);
+                if value < best {
+                    best = value;
+                    next = point;
+                }
+            }
+        }
+        center = next;
+        span = (span.0 / 2., span.1 / 2.);
+    }
+    if !best.is_finite() {
+        return None;
+    }
+
+    // Deviation raising S by one on both sides of the minimum
+    let deviation = |f: &dyn Fn(f64) -> f64, scale: f64| {
+        let side = |direction: f64| {
+            let mut step = scale * 1e-6;
+            while f(direction * step) < best + 1. && step < scale * 1e3 {
+                step *= 2.;
+            }
+            let (mut inside, mut outside) = (step / 2., step);
+            for _ in 0..50 {
+                let middle = (inside + outside) / 2.;
+                if f(direction * middle) < best + 1. {
+                    inside = middle;
+                } else {
+                    outside = middle;
+                }
+            }
+            (inside + outside) / 2.
+        };
+        (side(-1.) + side(1.)) / 2.
+    };
+    let alpha_c_error = deviation(&|delta| quality(center.0 + delta, center.1), high - low);
+    let nu = center.1.exp();
+    let nu_error = deviation(&|delta| quality(center.0, (nu + delta).max(1e-3).ln()), nu);
+    Some(Collapse {
+        alpha_c: center.0,
+        alpha_c_error,
+        nu,
+        nu_error,
+        quality: best,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rand_cnf::seeded_rng;
+    use crate::stats::wilson_interval;
+    use rand::Rng;
+
+    /// Exact curves of a logistic transition obeying finite-size scaling
+    fn curves(alpha_c: f64, nu: f64) -> Vec<Curve> {
+        [25, 50, 100, 200]
+            .into_iter()
+            .map(|n| Curve {
+                n,
+                points: (0..=150)
+                    .map(|i| {
+                        let alpha = 3.5 + i as f64 * 0.01;
+                        let share = 1. / (1. + scaling_variable(alpha, alpha_c, nu, n).exp());
+                        (alpha, share, 0.01)
+                    })
+                    .collect(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_collapse() {
+        let curves = curves(4.2, 1.5);
+        let collapse = collapse(&curves).unwrap();
+        assert!((collapse.alpha_c - 4.2).abs() < 1e-3);
+        assert!((collapse.nu - 1.5).abs() < 1e-2);
+        assert!(collapse.quality < 0.1);
+        assert!(collapse.alpha_c_error > 0. && collapse.nu_error > 0.);
+        // Any other parameters spread the curves apart
+        assert!(collapse_quality(&curves, 4.3, 1.5).unwrap() > 1.);
+        assert!(collapse_quality(&curves, 4.2, 2.5).unwrap() > 1.);
+    }
+
+    /// Curves of 5 samples per point for alpha from 2 to 7, from the
+    /// numbers of satisfiable formulas
+    fn coarse_curves(counts: [(u32, [u32; 6]); 2]) -> Vec<Curve> {
+        counts
+            .into_iter()
+            .map(|(n, counts)| Curve {
+                n,
+                points: counts
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &satisfiable)| {
+                        let (lower, upper) = wilson_interval(satisfiable, 5, 1.96);
+                        (2. + i as f64, satisfiable as f64 / 5., (upper - lower) / (2. * 1.96))
+                    })
+                    .collect(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_noisy_collapse() {
+        // Coarse curves with few points in the transition, the first ones
+        // from 3-colouring, where any step between 4 and 5 collapses them
+        // perfectly as ν vanishes: the search stays within its bounds
+        let mut rng = seeded_rng(Some(1));
+        let mut sample = |n| {
+            let mut counts = [0; 6];
+            for (i, count) in counts.iter_mut().enumerate() {
+                let share = 1. / (1. + scaling_variable(2. + i as f64, 4.5, 1.5, n).exp());
+                *count = (0..5).filter(|_| rng.gen_bool(share)).count() as u32;
+            }
+            (n, counts)
+        };
+        let random = [sample(10), sample(20)];
+        for curves in [coarse_curves([(10, [5, 4, 4, 0, 0, 0]), (20, [5, 5, 4, 0, 0, 0])]), coarse_curves(random)] {
+            let collapse = collapse(&curves).unwrap();
+            assert!((2. ..=7.).contains(&collapse.alpha_c));
+            assert!((NU_RANGE.0..=NU_RANGE.1).contains(&collapse.nu));
+            assert!(collapse.quality.is_finite() && collapse.alpha_c_error.is_finite() && collapse.nu_error.is_finite());
+        }
+    }
+
+    #[test]
+    fn test_no_transition() {
+        let flat = |n| Curve {
+            n,
+            points: vec![(1., 1., 0.01), (2., 1., 0.01)],
+        };
+        assert_eq!(None, collapse(&[flat(10), flat(20)]));
+    }
+}
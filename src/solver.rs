@@ -0,0 +1,40 @@
+use crate::cnf::{Assignment, CNFFormula};
+
+/// Counters describing the work done by a solver on a formula
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Statistics {
+    /// Number of branching decisions
+    pub decisions: u64,
+    /// Number of literals implied by unit propagation
+    pub propagations: u64,
+    /// Number of conflicts (falsified clauses) encountered
+    pub conflicts: u64,
+    /// Number of pure literals assigned by pure literal elimination
+    pub pure_literals: u64,
+    /// Number of times the search undid a branch (or backjumped)
+    pub backtracks: u64,
+    /// Maximum number of nested decisions
+    pub max_depth: u64,
+}
+
+/// Outcome of a solver on a formula
+#[derive(Clone, Debug)]
+pub struct Solution {
+    /// A satisfying assignment, or `None` if the formula is unsatisfiable
+    pub model: Option<Assignment>,
+    pub statistics: Statistics,
+}
+
+impl Solution {
+    pub fn is_sat(&self) -> bool {
+        self.model.is_some()
+    }
+}
+
+/// Common interface of the SAT solvers (shared between the worker threads
+/// of a sweep)
+pub trait Solver: Sync {
+    fn name(&self) -> &'static str;
+
+    fn solve(&self, formula: &CNFFormula) -> Solution;
+}
@@ -0,0 +1,309 @@
+use clap::ValueEnum;
+
+/// Arithmetic mean of a sample (NaN if it is empty)
+pub fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+/// Median of a sample, averaging the two middle values for samples of
+/// even size (NaN if it is empty)
+pub fn median(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return f64::NAN;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let middle = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        (sorted[middle - 1] + sorted[middle]) / 2.
+    } else {
+        sorted[middle]
+    }
+}
+
+/// Wilson score interval of a binomial proportion, from the number of
+/// successes among a number of trials and the quantile `z` of the normal
+/// distribution (1.96 for a 95% confidence level)
+///
+/// See <https://en.wikipedia.org/wiki/Binomial_proportion_confidence_interval#Wilson_score_interval>.
+pub fn wilson_interval(successes: u32, trials: u32, z: f64) -> (f64, f64) {
+    if trials == 0 {
+        return (0., 1.);
+    }
+    let n = trials as f64;
+    let p = successes as f64 / n;
+    let denominator = 1. + z * z / n;
+    let center = (p + z * z / (2. * n)) / denominator;
+    let half_width = z / denominator * (p * (1. - p) / n + z * z / (4. * n * n)).sqrt();
+    // The bounds reach 0 and 1 exactly, which rounding errors would miss
+    let lower = if successes == 0 { 0. } else { (center - half_width).max(0.) };
+    let upper = if successes >= trials { 1. } else { (center + half_width).min(1.) };
+    (lower, upper)
+}
+
+/// Method computing the confidence interval of a binomial proportion
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum Interval {
+    /// Wilson score interval
+    Wilson,
+    /// Exact interval from the quantiles of the beta distribution
+    ClopperPearson,
+}
+
+impl Interval {
+    /// Bounds of the interval with the given confidence level (e.g. 0.95)
+    pub fn bounds(self, successes: u32, trials: u32, confidence: f64) -> (f64, f64) {
+        match self {
+            Interval::Wilson => wilson_interval(successes, trials, normal_quantile(0.5 + confidence / 2.)),
+            Interval::ClopperPearson => clopper_pearson_interval(successes, trials, confidence),
+        }
+    }
+}
+
+/// Clopper-Pearson interval of a binomial proportion, whose bounds are
+/// quantiles of beta distributions, with the given confidence level
+///
+/// See <https://en.wikipedia.org/wiki/Binomial_proportion_confidence_interval#Clopper%E2%80%93Pearson_interval>.
+pub fn clopper_pearson_interval(successes: u32, trials: u32, confidence: f64) -> (f64, f64) {
+    let (x, n) = (successes as f64, trials as f64);
+    let alpha = 1. - confidence;
+    let lower = if successes == 0 {
+        0.
+    } else {
+        beta_quantile(alpha / 2., x, n - x + 1.)
+    };
+    let upper = if successes >= trials {
+        1.
+    } else {
+        beta_quantile(1. - alpha / 2., x + 1., n - x)
+    };
+    (lower, upper)
+}
+
+/// Cumulative distribution function of the standard normal distribution
+pub fn normal_cdf(x: f64) -> f64 {
+    erfc(-x / std::f64::consts::SQRT_2) / 2.
+}
+
+/// Complementary error function, with a relative error below 1.2e-7
+/// (Chebyshev approximation from Numerical Recipes, section 6.2)
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1. / (1. + z / 2.);
+    let polynomial = [
+        -1.26551223,
+        1.00002368,
+        0.37409196,
+        0.09678418,
+        -0.18628806,
+        0.27886807,
+        -1.13520398,
+        1.48851587,
+        -0.82215223,
+        0.17087277,
+    ]
+    .iter()
+    .rev()
+    .fold(0., |acc, coefficient| acc * t + coefficient);
+    let result = t * (-z * z + polynomial).exp();
+    if x >= 0. {
+        result
+    } else {
+        2. - result
+    }
+}
+
+/// Quantile function of the standard normal distribution, with a relative
+/// error below 1.2e-9 (Acklam's rational approximation)
+pub fn normal_quantile(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969683028665376e1,
+        2.209460984245205e2,
+        -2.759285104469687e2,
+        1.38357751867269e2,
+        -3.066479806614716e1,
+        2.506628277459239,
+    ];
+    const B: [f64; 5] = [
+        -5.447609879822406e1,
+        1.615858368580409e2,
+        -1.556989798598866e2,
+        6.680131188771972e1,
+        -1.328068155288572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784894002430293e-3,
+        -3.223964580411365e-1,
+        -2.400758277161838,
+        -2.549732539343734,
+        4.374664141464968,
+        2.938163982698783,
+    ];
+    const D: [f64; 4] = [
+        7.784695709041462e-3,
+        3.224671290700398e-1,
+        2.445134137142996,
+        3.754408661907416,
+    ];
+    let tail = |q: f64| {
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.)
+    };
+    if p <= 0. {
+        f64::NEG_INFINITY
+    } else if p >= 1. {
+        f64::INFINITY
+    } else if p < 0.02425 {
+        tail((-2. * p.ln()).sqrt())
+    } else if p > 1. - 0.02425 {
+        -tail((-2. * (1. - p).ln()).sqrt())
+    } else {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.)
+    }
+}
+
+/// Quantile function of the beta distribution, inverting its cumulative
+/// distribution function by bisection
+fn beta_quantile(p: f64, a: f64, b: f64) -> f64 {
+    let (mut low, mut high) = (0., 1.);
+    for _ in 0..100 {
+        let middle = (low + high) / 2.;
+        if regularized_beta(middle, a, b) < p {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    (low + high) / 2.
+}
+
+/// Regularized incomplete beta function I_x(a, b), the cumulative
+/// distribution function of the beta distribution, evaluated with its
+/// continued fraction (Numerical Recipes, section 6.4)
+fn regularized_beta(x: f64, a: f64, b: f64) -> f64 {
+    if x <= 0. {
+        return 0.;
+    }
+    if x >= 1. {
+        return 1.;
+    }
+    let front = (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1. - x).ln()).exp();
+    // The continued fraction converges quickly for x < (a + 1) / (a + b + 2)
+    if x < (a + 1.) / (a + b + 2.) {
+        front * beta_fraction(x, a, b) / a
+    } else {
+        1. - front * beta_fraction(1. - x, b, a) / b
+    }
+}
+
+/// Continued fraction of the incomplete beta function (modified Lentz's method)
+fn beta_fraction(x: f64, a: f64, b: f64) -> f64 {
+    const TINY: f64 = 1e-300;
+    let mut c = 1.;
+    let mut d = 1. - (a + b) * x / (a + 1.);
+    if d.abs() < TINY {
+        d = TINY;
+    }
+    d = 1. / d;
+    let mut result = d;
+    for m in 1..300 {
+        let m = m as f64;
+        let even = m * (b - m) * x / ((a + 2. * m - 1.) * (a + 2. * m));
+        let odd = -(a + m) * (a + b + m) * x / ((a + 2. * m) * (a + 2. * m + 1.));
+        for coefficient in [even, odd] {
+            d = 1. + coefficient * d;
+            if d.abs() < TINY {
+                d = TINY;
+            }
+            c = 1. + coefficient / c;
+            if c.abs() < TINY {
+                c = TINY;
+            }
+            d = 1. / d;
+            result *= d * c;
+        }
+        if (d * c - 1.).abs() < 1e-15 {
+            break;
+        }
+    }
+    result
+}
+
+/// Logarithm of the gamma function for positive arguments (Lanczos approximation)
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 6] = [
+        76.18009172947146,
+        -86.50532032941677,
+        24.01409824083091,
+        -1.231739572450155,
+        0.1208650973866179e-2,
+        -0.5395239384953e-5,
+    ];
+    let tmp = x + 5.5 - (x + 0.5) * (x + 5.5).ln();
+    let mut series = 1.000000000190015;
+    for (i, coefficient) in COEFFICIENTS.iter().enumerate() {
+        series += coefficient / (x + 1. + i as f64);
+    }
+    -tmp + (2.5066282746310005 * series / x).ln()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mean_median() {
+        assert_eq!(2.5, mean(&[1., 2., 3., 4.]));
+        assert_eq!(2.5, median(&[4., 1., 3., 2.]));
+        assert_eq!(3., median(&[10., 3., 1.]));
+        assert!(mean(&[]).is_nan());
+        assert!(median(&[]).is_nan());
+    }
+
+    #[test]
+    fn test_wilson_interval() {
+        let (lower, upper) = wilson_interval(50, 100, 1.96);
+        assert!((lower - 0.4038).abs() < 1e-4);
+        assert!((upper - 0.5962).abs() < 1e-4);
+        let (lower, upper) = wilson_interval(0, 10, 1.96);
+        assert_eq!(0., lower);
+        assert!((upper - 0.2775).abs() < 1e-4);
+        assert_eq!((0., 1.), wilson_interval(0, 0, 1.96));
+        for trials in 1..100 {
+            assert_eq!(0., wilson_interval(0, trials, 1.96).0);
+            assert_eq!(1., wilson_interval(trials, trials, 1.96).1);
+        }
+    }
+
+    #[test]
+    fn test_normal_cdf() {
+        assert!((normal_cdf(0.) - 0.5).abs() < 1e-7);
+        assert!((normal_cdf(1.959964) - 0.975).abs() < 1e-7);
+        assert!((normal_cdf(-1.) - 0.158655).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_normal_quantile() {
+        assert!((normal_quantile(0.975) - 1.959964).abs() < 1e-6);
+        assert!((normal_quantile(0.005) + 2.575829).abs() < 1e-6);
+        assert_eq!(0., normal_quantile(0.5));
+    }
+
+    #[test]
+    fn test_clopper_pearson_interval() {
+        let (lower, upper) = clopper_pearson_interval(0, 10, 0.95);
+        assert_eq!(0., lower);
+        assert!((upper - (1. - 0.025f64.powf(0.1))).abs() < 1e-9);
+        let (lower, upper) = clopper_pearson_interval(5, 10, 0.95);
+        assert!((lower - 0.187086).abs() < 1e-6);
+        assert!((upper - 0.812914).abs() < 1e-6);
+        assert_eq!((0., 1.), clopper_pearson_interval(0, 0, 0.95));
+        // Exact intervals are wider than Wilson intervals
+        let (wilson_lower, wilson_upper) = Interval::Wilson.bounds(30, 100, 0.95);
+        let (lower, upper) = Interval::ClopperPearson.bounds(30, 100, 0.95);
+        assert!(lower < wilson_lower && wilson_upper < upper);
+    }
+}
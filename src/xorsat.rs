@@ -0,0 +1,151 @@
+use rand::Rng;
+use std::fmt;
+
+use crate::cnf::Evaluation;
+use crate::rand_cnf::{random_variables, seeded_rng};
+
+/// A parity constraint: the sum modulo 2 (XOR) of the values of its
+/// variables must equal `parity`
+#[derive(Clone, Debug, PartialEq)]
+pub struct XorConstraint {
+    pub variables: Vec<u32>,
+    pub parity: bool,
+}
+
+impl XorConstraint {
+    /// Evaluate the constraint, which is undetermined as long as one of its
+    /// variables is unassigned
+    pub fn evaluate(&self, assignment: &[Option<bool>]) -> Evaluation {
+        let mut sum = false;
+        for &variable in self.variables.iter() {
+            match assignment.get(variable as usize).copied().flatten() {
+                Some(value) => sum ^= value,
+                None => return Evaluation::Undetermined,
+            }
+        }
+        if sum == self.parity {
+            Evaluation::Satisfied
+        } else {
+            Evaluation::Falsified
+        }
+    }
+}
+
+impl fmt::Display for XorConstraint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}={}",
+            self.variables
+                .iter()
+                .map(|variable| format!("x_{{{}}}", variable))
+                .collect::<Vec<String>>()
+                .join("⊕"),
+            self.parity as u8
+        )
+    }
+}
+
+/// A system of parity constraints, i.e. of linear equations over GF(2)
+#[derive(Clone, Debug, PartialEq)]
+pub struct XorFormula {
+    pub constraints: Vec<XorConstraint>,
+}
+
+impl XorFormula {
+    /// Number of variables (actually an upper bound, as variables
+    /// are counted from 0 up to the largest one in use)
+    pub fn num_variables(&self) -> usize {
+        self.constraints
+            .iter()
+            .flat_map(|constraint| constraint.variables.iter())
+            .max()
+            .map_or(0, |&x| x as usize + 1)
+    }
+
+    /// Evaluate the formula: falsified as soon as one constraint is,
+    /// satisfied when all constraints are
+    pub fn evaluate(&self, assignment: &[Option<bool>]) -> Evaluation {
+        let mut evaluation = Evaluation::Satisfied;
+        for constraint in self.constraints.iter() {
+            match constraint.evaluate(assignment) {
+                Evaluation::Falsified => return Evaluation::Falsified,
+                Evaluation::Undetermined => evaluation = Evaluation::Undetermined,
+                Evaluation::Satisfied => (),
+            }
+        }
+        evaluation
+    }
+}
+
+impl fmt::Display for XorFormula {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            self.constraints
+                .iter()
+                .map(|x| format!("({x})"))
+                .collect::<Vec<String>>()
+                .join("∧")
+        )
+    }
+}
+
+/// Generate a uniform random k-XORSAT formula: alpha·n constraints on k
+/// distinct variables drawn uniformly, with random parities
+pub fn generate_xor(k: u8, n: u32, alpha: f32, seed: Option<u64>) -> XorFormula {
+    assert!(n >= k.into());
+    let mut rng = seeded_rng(seed);
+    let constraints_cnt = (alpha * (n as f32)) as usize;
+    let constraints = (0..constraints_cnt)
+        .map(|_| XorConstraint {
+            variables: random_variables(&mut rng, k, n),
+            parity: rng.gen_bool(0.5),
+        })
+        .collect();
+    XorFormula { constraints }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gen() {
+        let formula = generate_xor(3, 10, 2., Some(42));
+        assert_eq!(20, formula.constraints.len());
+        for constraint in formula.constraints.iter() {
+            let mut variables = constraint.variables.clone();
+            variables.sort();
+            variables.dedup();
+            assert_eq!(3, variables.len());
+            assert!(variables.iter().all(|&variable| variable < 10));
+        }
+        assert_eq!(formula, generate_xor(3, 10, 2., Some(42)));
+        assert!(formula.constraints.iter().any(|constraint| constraint.parity));
+        assert!(formula.constraints.iter().any(|constraint| !constraint.parity));
+    }
+
+    #[test]
+    fn test_evaluate() {
+        // (x_{0}⊕x_{1}=1)∧(x_{1}⊕x_{2}=0)
+        let formula = XorFormula {
+            constraints: vec![
+                XorConstraint {
+                    variables: vec![0, 1],
+                    parity: true,
+                },
+                XorConstraint {
+                    variables: vec![1, 2],
+                    parity: false,
+                },
+            ],
+        };
+        assert_eq!("(x_{0}⊕x_{1}=1)∧(x_{1}⊕x_{2}=0)", formula.to_string());
+        assert_eq!(Evaluation::Undetermined, formula.evaluate(&[Some(true), Some(false), None]));
+        assert_eq!(Evaluation::Satisfied, formula.evaluate(&[Some(true), Some(false), Some(false)]));
+        assert_eq!(Evaluation::Falsified, formula.evaluate(&[Some(true), Some(true), None]));
+        assert_eq!(Evaluation::Falsified, formula.evaluate(&[Some(false), Some(true), Some(false)]));
+    }
+}